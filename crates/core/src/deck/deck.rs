@@ -46,6 +46,11 @@ impl<T: Card> Deck<T> {
     pub fn add_card(&mut self, card: T) {
         self.cards.push(card)
     }
+    /// slides a card under the deck so it is drawn last
+    #[inline]
+    pub fn add_card_bottom(&mut self, card: T) {
+        self.cards.insert(0, card)
+    }
     /// returns the amount of cards left inside deck
     #[inline]
     pub fn deck_size(&self) -> usize {
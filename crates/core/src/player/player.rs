@@ -1,6 +1,10 @@
-use {crate::cards::red_card::RedCard, std::fmt};
+use {
+    crate::cards::red_card::RedCard,
+    serde::{Deserialize, Serialize},
+    std::fmt,
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Serialize, Deserialize)]
 pub struct PlayerId(pub usize);
 
 impl fmt::Display for PlayerId {
@@ -9,7 +13,7 @@ impl fmt::Display for PlayerId {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct PlayerHand {
     cards: Vec<RedCard>,
 }
@@ -43,4 +47,9 @@ impl PlayerHand {
     pub fn len(&self) -> usize {
         self.cards.len()
     }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
 }
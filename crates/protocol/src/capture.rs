@@ -0,0 +1,347 @@
+use {
+    crate::{
+        codec::{CorrelationId, Frame},
+        error::ProtoError,
+        handshake::Handshake,
+        header::{Flags, Kind},
+        message::Message,
+        payload::PayloadFormat,
+    },
+    bytes::Bytes,
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::HashMap,
+        fs::File,
+        io::{self, BufRead, BufReader, BufWriter, Write},
+        path::Path,
+        sync::mpsc::{self, Receiver, Sender},
+        thread,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    thiserror::Error,
+};
+
+/// Leading bytes of every capture file, followed by the file format version.
+pub const CAPTURE_MAGIC: [u8; 4] = *b"A2AC";
+const CAPTURE_VERSION: u8 = 1;
+
+/// Failure to write or read a capture file.
+#[derive(Debug, Error)]
+pub enum CaptureError {
+    #[error("not a frame capture, or one written by an incompatible version")]
+    NotACapture,
+    #[error(transparent)]
+    Encode(#[from] bincode::error::EncodeError),
+    #[error(transparent)]
+    Decode(#[from] bincode::error::DecodeError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Which way a captured frame travelled, seen from the side that captured it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+/// A frame as the codec handed it out or took it in: reassembled,
+/// decompressed and with its correlation ID split off.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    /// Microseconds since the Unix epoch.
+    pub at_micros: u64,
+    pub direction: Direction,
+    /// Connection the frame belongs to, numbered by the capturing side.
+    pub stream: u32,
+    pub kind: u8,
+    pub flags: u8,
+    pub correlation: Option<CorrelationId>,
+    pub payload: Bytes,
+}
+
+impl Record {
+    /// Rebuilds the captured frame.
+    pub fn frame(&self) -> Result<Frame, ProtoError> {
+        let kind = Kind::try_from(self.kind)?;
+        let frame = Frame::new(
+            kind,
+            Flags::from_bits_truncate(self.flags),
+            self.payload.clone(),
+        );
+        Ok(match self.correlation {
+            Some(id) => frame.with_correlation(id),
+            None => frame,
+        })
+    }
+}
+
+enum Command {
+    Record(Record),
+    Flush(Sender<()>),
+}
+
+/// Appends every frame a codec encodes or decodes to a capture file.
+///
+/// Records are handed to a writer thread, so recording never blocks the
+/// executor. Clones share the file, so one capture can record many
+/// connections, each under its own `stream` number.
+#[derive(Debug, Clone)]
+pub struct Capture {
+    commands: Sender<Command>,
+    stream: u32,
+}
+
+impl Capture {
+    /// Creates or truncates the capture file at `path`.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, CaptureError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(&CAPTURE_MAGIC)?;
+        writer.write_all(&[CAPTURE_VERSION])?;
+        writer.flush()?;
+        let (commands, received) = mpsc::channel();
+        thread::Builder::new()
+            .name(String::from("capture"))
+            .spawn(move || {
+                if let Err(e) = write_records(writer, received) {
+                    tracing::warn!("Stopped capturing frames: {e}");
+                }
+            })?;
+        Ok(Self {
+            commands,
+            stream: 0,
+        })
+    }
+
+    /// The same capture, recording under another stream number.
+    #[inline]
+    pub fn stream(&self, stream: u32) -> Self {
+        Self {
+            commands: self.commands.clone(),
+            stream,
+        }
+    }
+
+    /// Queues `frame` for the writer thread, failing once it has stopped.
+    pub fn record(&self, direction: Direction, frame: &Frame) -> Result<(), CaptureError> {
+        let at_micros = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_micros() as u64);
+        let header = frame.header();
+        let record = Record {
+            at_micros,
+            direction,
+            stream: self.stream,
+            kind: header.kind() as u8,
+            flags: header.flags().bits(),
+            correlation: frame.correlation(),
+            payload: frame.payload().clone(),
+        };
+        self.commands
+            .send(Command::Record(record))
+            .map_err(|_| stopped().into())
+    }
+
+    /// Waits until every record queued so far is on disk.
+    pub fn flush(&self) -> Result<(), CaptureError> {
+        let (done, flushed) = mpsc::channel();
+        self.commands
+            .send(Command::Flush(done))
+            .map_err(|_| CaptureError::from(stopped()))?;
+        flushed.recv().map_err(|_| stopped().into())
+    }
+}
+
+fn stopped() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "capture writer stopped")
+}
+
+/// Writes records until every `Capture` is dropped, flushing whenever the
+/// queue runs empty so a capture survives a crash.
+fn write_records(
+    mut writer: BufWriter<File>,
+    commands: Receiver<Command>,
+) -> Result<(), CaptureError> {
+    while let Ok(command) = commands.recv() {
+        let mut next = Some(command);
+        while let Some(command) = next {
+            match command {
+                Command::Record(record) => {
+                    bincode::serde::encode_into_std_write(
+                        &record,
+                        &mut writer,
+                        bincode::config::standard(),
+                    )?;
+                }
+                Command::Flush(done) => {
+                    writer.flush()?;
+                    let _ = done.send(());
+                }
+            }
+            next = commands.try_recv().ok();
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Reads the records of a capture file in order.
+#[derive(Debug)]
+pub struct CaptureReader<R> {
+    reader: R,
+}
+
+impl CaptureReader<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CaptureError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: BufRead> CaptureReader<R> {
+    /// Checks the file header and positions the reader on the first record.
+    pub fn new(mut reader: R) -> Result<Self, CaptureError> {
+        let mut preamble = [0; 5];
+        reader.read_exact(&mut preamble)?;
+        if preamble[..4] != CAPTURE_MAGIC || preamble[4] != CAPTURE_VERSION {
+            return Err(CaptureError::NotACapture);
+        }
+        Ok(Self { reader })
+    }
+}
+
+impl<R: BufRead> Iterator for CaptureReader<R> {
+    type Item = Result<Record, CaptureError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.fill_buf() {
+            Ok([]) => None,
+            Ok(_) => Some(
+                bincode::serde::decode_from_std_read(&mut self.reader, bincode::config::standard())
+                    .map_err(CaptureError::from),
+            ),
+            Err(e) => Some(Err(e.into())),
+        }
+    }
+}
+
+/// Turns records into readable lines, following the payload format each
+/// stream negotiated in its handshake.
+#[derive(Debug, Default)]
+pub struct Inspector {
+    formats: HashMap<u32, PayloadFormat>,
+}
+
+impl Inspector {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Payload format `stream` settled on, JSON until its handshake is seen.
+    #[inline]
+    pub fn format(&self, stream: u32) -> PayloadFormat {
+        self.formats.get(&stream).copied().unwrap_or_default()
+    }
+
+    /// Decodes the payload of `record`, or explains why it could not.
+    pub fn describe(&mut self, record: &Record) -> String {
+        match record.frame() {
+            Ok(frame) => self.describe_frame(record.stream, &frame),
+            Err(e) => format!("unreadable frame: {e}"),
+        }
+    }
+
+    /// Decodes the payload of a frame seen on `stream`.
+    pub fn describe_frame(&mut self, stream: u32, frame: &Frame) -> String {
+        if frame.header().kind() == Kind::Control
+            && let Ok(handshake) = serde_json::from_slice::<Handshake>(frame.payload())
+        {
+            if let Handshake::Accept(session) = &handshake {
+                self.formats.insert(stream, session.format);
+            }
+            return format!("{handshake:?}");
+        }
+        match Message::decode_with(frame, &self.format(stream)) {
+            Ok(msg) => format!("{msg:?}"),
+            Err(e) => format!("{} undecoded bytes ({e})", frame.payload().len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Capture, CaptureReader, Direction, Inspector},
+        crate::{
+            codec::{CorrelationId, Frame, FrameCodec},
+            header::{Flags, Kind},
+            message::Message,
+        },
+        bytes::{Bytes, BytesMut},
+        tokio_util::codec::{Decoder, Encoder},
+    };
+
+    #[test]
+    fn records_both_directions() {
+        let path = std::env::temp_dir().join(format!("apples-capture-{}", std::process::id()));
+        let capture = Capture::create(&path).expect("create");
+        let mut sender = FrameCodec::with_default_limit().with_capture(capture.stream(1));
+        let mut receiver = FrameCodec::with_default_limit().with_capture(capture.stream(2));
+
+        let msg = Message::JoinRequest {
+            name: String::from("Ada"),
+        };
+        let frame = msg
+            .encode()
+            .expect("encode")
+            .with_correlation(CorrelationId(9));
+        let mut wire = BytesMut::new();
+        sender.encode(frame, &mut wire).expect("send");
+        receiver.decode(&mut wire).expect("receive").expect("frame");
+        capture.flush().expect("flush");
+
+        let records = CaptureReader::open(&path)
+            .expect("open")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("records");
+        std::fs::remove_file(&path).expect("cleanup");
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            (records[0].direction, records[0].stream),
+            (Direction::Outbound, 1)
+        );
+        assert_eq!(
+            (records[1].direction, records[1].stream),
+            (Direction::Inbound, 2)
+        );
+        assert_eq!(records[1].correlation, Some(CorrelationId(9)));
+        assert_eq!(records[0].payload, records[1].payload);
+
+        let mut inspector = Inspector::new();
+        assert_eq!(inspector.describe(&records[1]), format!("{msg:?}"));
+    }
+
+    #[test]
+    fn skips_frames_that_fail_to_encode() {
+        let path =
+            std::env::temp_dir().join(format!("apples-capture-failed-{}", std::process::id()));
+        let capture = Capture::create(&path).expect("create");
+        let mut codec = FrameCodec::new(4).with_capture(capture.clone());
+
+        let mut wire = BytesMut::new();
+        let too_large = Frame::new(Kind::Game, Flags::empty(), Bytes::from_static(b"Juicy"));
+        assert!(codec.encode(too_large, &mut wire).is_err());
+        let ping = Frame::new(Kind::Control, Flags::empty(), Bytes::from_static(b"ping"));
+        codec.encode(ping, &mut wire).expect("encode");
+        capture.flush().expect("flush");
+
+        let records = CaptureReader::open(&path)
+            .expect("open")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("records");
+        std::fs::remove_file(&path).expect("cleanup");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind, Kind::Control as u8);
+    }
+}
@@ -1,26 +1,45 @@
 use {
     crate::{
-        error::ProtoError,
-        header::{Header, Kind},
+        capture::{Capture, Direction},
+        error::{HeaderError, ProtoError},
+        header::{Flags, Header, Kind, MAGIC, VERSION},
+        metrics::CodecMetrics,
     },
-    bytes::{Bytes, BytesMut},
+    bytes::{Buf, BufMut, Bytes, BytesMut},
+    serde::{Deserialize, Serialize},
+    std::{fmt, sync::Arc, time::Instant},
     tokio_util::codec::{Decoder, Encoder},
 };
 
+/// Ties a reply to the request it answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CorrelationId(pub u32);
+
+impl fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
 /// Zero-copy frame consisting of a validated header and a payload slice.
 #[derive(Debug, Clone)]
 pub struct Frame {
     header: Header,
     payload: Bytes,
+    correlation: Option<CorrelationId>,
 }
 
 impl Frame {
     /// Creates a frame using the provided kind, flags and payload.
     #[inline]
-    pub fn new(kind: Kind, flags: u8, payload: Bytes) -> Self {
+    pub fn new(kind: Kind, flags: Flags, payload: Bytes) -> Self {
         debug_assert!(payload.len() <= u32::MAX as usize);
         let header = Header::new(kind, flags, payload.len() as u32);
-        Self { header, payload }
+        Self {
+            header,
+            payload,
+            correlation: None,
+        }
     }
 
     /// Reconstructs a frame from raw parts, ensuring the payload length matches the header.
@@ -31,7 +50,18 @@ impl Frame {
                 actual: payload.len(),
             });
         }
-        Ok(Self { header, payload })
+        Ok(Self {
+            header,
+            payload,
+            correlation: None,
+        })
+    }
+
+    /// Tags the frame with a correlation ID.
+    #[inline]
+    pub fn with_correlation(mut self, id: CorrelationId) -> Self {
+        self.correlation = Some(id);
+        self
     }
 
     #[inline]
@@ -44,23 +74,176 @@ impl Frame {
         &self.payload
     }
 
+    #[inline]
+    pub fn correlation(&self) -> Option<CorrelationId> {
+        self.correlation
+    }
+
     #[inline]
     pub fn into_payload(self) -> Bytes {
         self.payload
     }
+
+    /// Moves the prefixed correlation ID of a decoded frame into its own field.
+    fn split_correlation(mut self) -> Result<Self, ProtoError> {
+        let flags = self.header.flags();
+        if !flags.contains(Flags::CORRELATED) {
+            return Ok(self);
+        }
+        if self.payload.len() < CORRELATION_SIZE {
+            return Err(ProtoError::MissingCorrelation);
+        }
+        let id = CorrelationId(self.payload.get_u32());
+        self.header = self
+            .header
+            .with_flags(flags - Flags::CORRELATED)
+            .with_payload_len(self.payload.len() as u32);
+        Ok(self.with_correlation(id))
+    }
+
+    /// Payload as sent on the wire, with the correlation ID in front if there is one.
+    fn wire_payload(self) -> (Flags, Bytes) {
+        let flags = self.header.flags() - Flags::CORRELATED;
+        let Some(CorrelationId(id)) = self.correlation else {
+            return (flags, self.payload);
+        };
+        let mut payload = BytesMut::with_capacity(CORRELATION_SIZE + self.payload.len());
+        payload.put_u32(id);
+        payload.extend_from_slice(&self.payload);
+        (flags | Flags::CORRELATED, payload.freeze())
+    }
+}
+
+/// Size of the correlation ID that prefixes correlated payloads.
+pub const CORRELATION_SIZE: usize = 4;
+
+/// Size of the CRC32C trailer appended to checksummed payloads.
+pub const CHECKSUM_SIZE: usize = 4;
+
+/// Largest message reassembled from fragments once fragmentation is enabled.
+pub const MAX_MESSAGE_LEN: u32 = 16 << 20;
+
+/// Payloads above this many bytes are compressed once compression is enabled.
+pub const COMPRESSION_THRESHOLD: usize = 512;
+
+/// Running totals kept by the decoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecoderStats {
+    /// Frames successfully decoded.
+    pub frames: u64,
+    /// Garbage bytes dropped while resynchronising.
+    pub skipped_bytes: u64,
+    /// Frames discarded in resync mode because their checksum did not match.
+    pub dropped_frames: u64,
+}
+
+impl DecoderStats {
+    /// Totals before anything was decoded.
+    pub const ZERO: Self = Self {
+        frames: 0,
+        skipped_bytes: 0,
+        dropped_frames: 0,
+    };
+}
+
+impl Default for DecoderStats {
+    #[inline]
+    fn default() -> Self {
+        Self::ZERO
+    }
 }
 
 /// Tokio codec that performs zero-copy framing for Apples-to-Apples protocol packets.
+///
+/// Until a version is negotiated, Control frames of any version are accepted so
+/// peers can exchange their hellos; every other frame must carry the codec's version.
+///
+/// Compressed payloads are always inflated on decode, with `max_payload_len`
+/// applied to the inflated size, and checksum trailers are always verified.
+///
+/// With fragmentation enabled, payloads above `max_payload_len` are split
+/// across frames and only the last one carries `FINAL_FRAGMENT`; the decoder
+/// buffers fragments up to `max_message_len` bytes. Control frames are never
+/// fragmented and may arrive between the fragments of another message.
+///
+/// In resync mode a malformed header no longer ends the stream: the decoder
+/// drops bytes up to the next `MAGIC` and carries on.
+///
+/// Clones share their `CodecMetrics`, if any, with the original.
 #[derive(Debug, Clone)]
 pub struct FrameCodec {
     max_payload_len: u32,
+    version: u16,
+    negotiated: bool,
+    compress_above: Option<usize>,
+    checksum: bool,
+    resync: bool,
+    max_message_len: Option<u32>,
+    partial: Option<(Header, BytesMut)>,
+    stats: DecoderStats,
+    capture: Option<Capture>,
+    metrics: Option<Arc<CodecMetrics>>,
 }
 
 impl FrameCodec {
     /// Creates a codec with the provided maximum payload length in bytes.
     #[inline]
     pub const fn new(max_payload_len: u32) -> Self {
-        Self { max_payload_len }
+        Self {
+            max_payload_len,
+            version: VERSION,
+            negotiated: false,
+            compress_above: None,
+            checksum: false,
+            resync: false,
+            max_message_len: None,
+            partial: None,
+            stats: DecoderStats::ZERO,
+            capture: None,
+            metrics: None,
+        }
+    }
+
+    /// Appends a CRC32C trailer to every outgoing payload.
+    #[inline]
+    pub const fn with_checksum(mut self) -> Self {
+        self.checksum = true;
+        self
+    }
+
+    /// Skips over corrupt input instead of failing on it.
+    #[inline]
+    pub const fn with_resync(mut self) -> Self {
+        self.resync = true;
+        self
+    }
+
+    /// Splits and reassembles messages of up to `max_message_len` bytes.
+    #[inline]
+    pub const fn with_fragmentation(mut self, max_message_len: u32) -> Self {
+        self.max_message_len = Some(max_message_len);
+        self
+    }
+
+    /// Compresses outgoing payloads larger than `threshold` bytes.
+    #[inline]
+    pub const fn with_compression(mut self, threshold: usize) -> Self {
+        self.compress_above = Some(threshold);
+        self
+    }
+
+    /// Records every frame encoded or decoded to `capture`.
+    #[inline]
+    pub fn with_capture(mut self, capture: Capture) -> Self {
+        self.capture = Some(capture);
+        self
+    }
+
+    /// Counts traffic, header errors and encode time in `metrics`.
+    #[inline]
+    pub fn with_metrics(mut self, metrics: Arc<CodecMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
     }
 
     /// Creates a codec with a conservative default payload ceiling (1 MiB).
@@ -69,6 +252,84 @@ impl FrameCodec {
         Self::new(1 << 20)
     }
 
+    /// Wire version written into outgoing headers.
+    #[inline]
+    pub const fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Whether a version was negotiated for this session.
+    #[inline]
+    pub const fn is_negotiated(&self) -> bool {
+        self.negotiated
+    }
+
+    /// Locks the codec to the negotiated version for the rest of the session.
+    #[inline]
+    pub fn set_version(&mut self, version: u16) {
+        self.version = version;
+        self.negotiated = true;
+    }
+
+    /// Enables or disables compression of outgoing payloads.
+    #[inline]
+    pub fn set_compression(&mut self, threshold: Option<usize>) {
+        self.compress_above = threshold;
+    }
+
+    /// Enables or disables checksum trailers on outgoing payloads.
+    #[inline]
+    pub fn set_checksum(&mut self, enabled: bool) {
+        self.checksum = enabled;
+    }
+
+    /// Enables or disables resync mode.
+    #[inline]
+    pub fn set_resync(&mut self, enabled: bool) {
+        self.resync = enabled;
+    }
+
+    /// Enables fragmentation with the given message limit, or disables it.
+    ///
+    /// Disabling it drops any message that is halfway reassembled.
+    #[inline]
+    pub fn set_fragmentation(&mut self, max_message_len: Option<u32>) {
+        self.max_message_len = max_message_len;
+        if max_message_len.is_none() {
+            self.partial = None;
+        }
+    }
+
+    /// Starts or stops recording frames.
+    #[inline]
+    pub fn set_capture(&mut self, capture: Option<Capture>) {
+        self.capture = capture;
+    }
+
+    /// Totals gathered by the decoder so far.
+    #[inline]
+    pub fn stats(&self) -> &DecoderStats {
+        &self.stats
+    }
+
+    /// Counters given to `with_metrics`, kept up to date as the codec works.
+    #[inline]
+    pub fn metrics(&self) -> Option<&Arc<CodecMetrics>> {
+        self.metrics.as_ref()
+    }
+
+    #[inline]
+    fn ensure_version(&self, header: &Header) -> Result<(), HeaderError> {
+        let hello = !self.negotiated && header.kind() == Kind::Control;
+        if !hello && header.version() != self.version {
+            return Err(HeaderError::UnsupportedVersion {
+                found: header.version(),
+                expected: self.version,
+            });
+        }
+        Ok(())
+    }
+
     #[inline]
     fn ensure_within_limit(&self, length: u32) -> Result<(), ProtoError> {
         if length > self.max_payload_len {
@@ -81,17 +342,98 @@ impl FrameCodec {
     }
 }
 
-impl Default for FrameCodec {
-    fn default() -> Self {
-        Self::with_default_limit()
+impl FrameCodec {
+    /// Compressed payload, if compression is enabled and actually saves space.
+    fn compress(&self, payload: &[u8]) -> Option<Bytes> {
+        let threshold = self.compress_above?;
+        if payload.len() <= threshold {
+            return None;
+        }
+        let packed = lz4_flex::compress_prepend_size(payload);
+        (packed.len() < payload.len()).then(|| Bytes::from(packed))
     }
-}
 
-impl Decoder for FrameCodec {
-    type Item = Frame;
-    type Error = ProtoError;
+    /// Inflates a payload, checking the announced size before allocating.
+    fn decompress(&self, payload: &[u8]) -> Result<Bytes, ProtoError> {
+        let (len, packed) = lz4_flex::block::uncompressed_size(payload)?;
+        self.ensure_within_limit(u32::try_from(len).unwrap_or(u32::MAX))?;
+        Ok(Bytes::from(lz4_flex::block::decompress(packed, len)?))
+    }
 
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+    /// Drops everything before the next `MAGIC`, keeping a possible partial
+    /// magic at the end of the buffer.
+    fn skip_to_magic(&mut self, src: &mut BytesMut) {
+        let skip = src
+            .windows(MAGIC.len())
+            .position(|window| window == MAGIC)
+            .unwrap_or_else(|| src.len().saturating_sub(MAGIC.len() - 1));
+        if skip > 0 {
+            src.advance(skip);
+            self.stats.skipped_bytes += skip as u64;
+        }
+    }
+
+    /// Buffers a fragment, returning the whole message once its last fragment arrives.
+    fn reassemble(&mut self, frame: Frame) -> Result<Option<Frame>, ProtoError> {
+        let Some(max) = self.max_message_len else {
+            return Ok(Some(frame));
+        };
+        let header = frame.header();
+        let last = header.flags().contains(Flags::FINAL_FRAGMENT);
+        let header = header.with_flags(header.flags() - Flags::FINAL_FRAGMENT);
+        if header.kind() == Kind::Control {
+            return Frame::from_parts(header, frame.into_payload()).map(Some);
+        }
+
+        let (first, mut buffer) = match self.partial.take() {
+            Some(partial) => partial,
+            None if last => return Frame::from_parts(header, frame.into_payload()).map(Some),
+            None => (header, BytesMut::new()),
+        };
+        if first.kind() != header.kind() {
+            return Err(ProtoError::InterleavedFragment {
+                expected: first.kind(),
+                found: header.kind(),
+            });
+        }
+        let len = buffer.len() + frame.payload().len();
+        if len > max as usize {
+            return Err(ProtoError::MessageTooLarge { len, max });
+        }
+        buffer.extend_from_slice(frame.payload());
+
+        if !last {
+            self.partial = Some((first, buffer));
+            return Ok(None);
+        }
+        let header = first.with_payload_len(len as u32);
+        Frame::from_parts(header, buffer.freeze()).map(Some)
+    }
+
+    /// Writes one wire frame, compressing and checksumming its payload as configured.
+    fn encode_frame(&self, kind: Kind, mut flags: Flags, mut payload: Bytes, dst: &mut BytesMut) {
+        if let Some(packed) = self.compress(&payload) {
+            flags |= Flags::COMPRESSED;
+            payload = packed;
+        }
+        if self.checksum {
+            flags |= Flags::CHECKSUM;
+        }
+
+        let header = Header::new(kind, flags, payload.len() as u32).with_version(self.version);
+
+        dst.reserve(header.frame_len());
+        dst.extend_from_slice(header.as_bytes());
+        dst.extend_from_slice(&payload);
+        if self.checksum {
+            dst.extend_from_slice(&crc32c::crc32c(&payload).to_be_bytes());
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.frame_out(kind, header.frame_len());
+        }
+    }
+
+    fn decode_frame(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, ProtoError> {
         const HEADER_SIZE: usize = Header::SIZE;
 
         if src.len() < HEADER_SIZE {
@@ -99,47 +441,188 @@ impl Decoder for FrameCodec {
         }
 
         let header = Header::parse(&src[..HEADER_SIZE])?;
+        self.ensure_version(&header)?;
         self.ensure_within_limit(header.payload_len())?;
 
+        let mut flags = header.flags();
         let payload_len = header.payload_len() as usize;
-        let total_len =
-            HEADER_SIZE
-                .checked_add(payload_len)
-                .ok_or(ProtoError::PayloadTooLarge {
-                    len: header.payload_len(),
-                    max: self.max_payload_len,
-                })?;
+        let trailer_len = if flags.contains(Flags::CHECKSUM) {
+            CHECKSUM_SIZE
+        } else {
+            0
+        };
+        let total_len = HEADER_SIZE
+            .checked_add(payload_len)
+            .and_then(|len| len.checked_add(trailer_len))
+            .ok_or(ProtoError::PayloadTooLarge {
+                len: header.payload_len(),
+                max: self.max_payload_len,
+            })?;
 
         if src.len() < total_len {
             return Ok(None);
         }
 
         let mut frame = src.split_to(total_len);
-        let payload = frame.split_off(HEADER_SIZE).freeze();
+        if let Some(metrics) = &self.metrics {
+            metrics.frame_in(header.kind(), total_len);
+        }
+        let mut payload = frame.split_off(HEADER_SIZE);
+
+        if flags.contains(Flags::CHECKSUM) {
+            let trailer = payload.split_off(payload_len);
+            let expected = u32::from_be_bytes(
+                trailer[..]
+                    .try_into()
+                    .expect("trailer is CHECKSUM_SIZE bytes"),
+            );
+            let found = crc32c::crc32c(&payload);
+            if expected != found {
+                return Err(ProtoError::ChecksumMismatch { expected, found });
+            }
+            flags -= Flags::CHECKSUM;
+        }
 
+        let mut payload = payload.freeze();
+        if flags.contains(Flags::COMPRESSED) {
+            payload = self.decompress(&payload)?;
+            flags -= Flags::COMPRESSED;
+        }
+
+        let header = header
+            .with_flags(flags)
+            .with_payload_len(payload.len() as u32);
         Frame::from_parts(header, payload).map(Some)
     }
 }
 
+impl Default for FrameCodec {
+    fn default() -> Self {
+        Self::with_default_limit()
+    }
+}
+
+impl Decoder for FrameCodec {
+    type Item = Frame;
+    type Error = ProtoError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            if self.resync {
+                self.skip_to_magic(src);
+            }
+            match self.decode_frame(src) {
+                Ok(Some(frame)) => {
+                    if let Some(frame) = self.reassemble(frame)? {
+                        self.stats.frames += 1;
+                        let frame = frame.split_correlation()?;
+                        self.capture(Direction::Inbound, &frame);
+                        return Ok(Some(frame));
+                    }
+                }
+                Err(ProtoError::Header(e)) => {
+                    if let Some(metrics) = &self.metrics {
+                        metrics.header_error(&e);
+                    }
+                    if !self.resync {
+                        return Err(e.into());
+                    }
+                    src.advance(1);
+                    self.stats.skipped_bytes += 1;
+                }
+                Err(ProtoError::ChecksumMismatch { .. }) if self.resync => {
+                    self.stats.dropped_frames += 1;
+                }
+                other => return other,
+            }
+        }
+    }
+}
+
 impl Encoder<Frame> for FrameCodec {
     type Error = ProtoError;
 
     fn encode(&mut self, item: Frame, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        let payload_len = item.payload.len();
-        let payload_len_u32 =
-            u32::try_from(payload_len).map_err(|_| ProtoError::PayloadTooLarge {
-                len: u32::MAX,
-                max: self.max_payload_len,
-            })?;
+        let Some(metrics) = self.metrics.clone() else {
+            return self.encode_message(item, dst);
+        };
+        let started = Instant::now();
+        let encoded = self.encode_message(item, dst);
+        metrics.encoded(started.elapsed());
+        encoded
+    }
+}
 
-        self.ensure_within_limit(payload_len_u32)?;
+impl FrameCodec {
+    /// Encodes `item` and records it once it made it into `dst`.
+    fn encode_message(&mut self, item: Frame, dst: &mut BytesMut) -> Result<(), ProtoError> {
+        let captured = self.capture.is_some().then(|| item.clone());
+        self.encode_frames(item, dst)?;
+        if let Some(frame) = captured {
+            self.capture(Direction::Outbound, &frame);
+        }
+        Ok(())
+    }
 
-        let header = Header::new(item.header.kind(), item.header.flags(), payload_len_u32);
+    /// Records `frame`, giving up on the capture rather than the stream if that fails.
+    fn capture(&mut self, direction: Direction, frame: &Frame) {
+        if let Some(capture) = &self.capture
+            && let Err(e) = capture.record(direction, frame)
+        {
+            tracing::warn!("Disabling frame capture: {e}");
+            self.capture = None;
+        }
+    }
 
-        dst.reserve(Header::SIZE + payload_len);
-        dst.extend_from_slice(header.as_bytes());
-        dst.extend_from_slice(&item.payload);
+    /// Writes `item` as one frame, or as fragments if it is too large for one.
+    fn encode_frames(&mut self, item: Frame, dst: &mut BytesMut) -> Result<(), ProtoError> {
+        let kind = item.header.kind();
+        let (flags, payload) = item.wire_payload();
+        // The codec decides on compression and checksums itself.
+        let mut flags = flags - (Flags::COMPRESSED | Flags::CHECKSUM | Flags::FINAL_FRAGMENT);
+        let payload_len = payload.len();
+
+        let Some(max_message_len) = self.max_message_len else {
+            let payload_len_u32 =
+                u32::try_from(payload_len).map_err(|_| ProtoError::PayloadTooLarge {
+                    len: u32::MAX,
+                    max: self.max_payload_len,
+                })?;
+            self.ensure_within_limit(payload_len_u32)?;
+            self.encode_frame(kind, flags, payload, dst);
+            return Ok(());
+        };
+
+        flags |= Flags::FINAL_FRAGMENT;
+        let fragment_len = self.max_payload_len as usize;
+        if payload_len <= fragment_len {
+            self.encode_frame(kind, flags, payload, dst);
+            return Ok(());
+        }
+        if kind == Kind::Control {
+            return Err(ProtoError::PayloadTooLarge {
+                len: u32::try_from(payload_len).unwrap_or(u32::MAX),
+                max: self.max_payload_len,
+            });
+        }
+        if payload_len > max_message_len as usize {
+            return Err(ProtoError::MessageTooLarge {
+                len: payload_len,
+                max: max_message_len,
+            });
+        }
 
+        let mut start = 0;
+        while start < payload_len {
+            let end = payload_len.min(start + fragment_len);
+            let flags = if end == payload_len {
+                flags
+            } else {
+                flags - Flags::FINAL_FRAGMENT
+            };
+            self.encode_frame(kind, flags, payload.slice(start..end), dst);
+            start = end;
+        }
         Ok(())
     }
 }
@@ -147,9 +630,14 @@ impl Encoder<Frame> for FrameCodec {
 #[cfg(test)]
 mod tests {
     use {
-        super::{Frame, FrameCodec},
-        crate::header::{Header, Kind, MAGIC, VERSION},
+        super::{CorrelationId, DecoderStats, Frame, FrameCodec, CHECKSUM_SIZE},
+        crate::{
+            error::{HeaderError, ProtoError},
+            header::{Flags, Header, Kind, MAGIC, VERSION},
+            metrics::CodecMetrics,
+        },
         bytes::{Bytes, BytesMut},
+        std::sync::Arc,
         tokio_util::codec::{Decoder, Encoder},
     };
 
@@ -157,12 +645,12 @@ mod tests {
     fn roundtrip_zero_copy() {
         let mut codec = FrameCodec::default();
         let payload = Bytes::from_static(b"payload");
-        let frame = Frame::new(Kind::Game, 0, payload.clone());
+        let frame = Frame::new(Kind::Game, Flags::empty(), payload.clone());
 
         let mut buffer = BytesMut::new();
         codec.encode(frame.clone(), &mut buffer).expect("encode");
 
-        let expected_header = Header::new(Kind::Game, 0, payload.len() as u32);
+        let expected_header = Header::new(Kind::Game, Flags::empty(), payload.len() as u32);
         let mut expected = BytesMut::from(expected_header.as_bytes());
         expected.extend_from_slice(&payload);
 
@@ -171,10 +659,292 @@ mod tests {
         let decoded = codec.decode(&mut buffer).expect("decode").expect("frame");
         assert_eq!(decoded.payload(), &payload);
         assert_eq!(decoded.header().kind(), Kind::Game);
-        assert_eq!(decoded.header().flags(), 0);
+        assert_eq!(decoded.header().flags(), Flags::empty());
         assert_eq!(decoded.header().version(), VERSION);
         assert_eq!(decoded.header().as_bytes()[..4], MAGIC);
 
         assert!(buffer.is_empty());
     }
+
+    #[test]
+    fn compresses_large_payloads() {
+        let mut codec = FrameCodec::default().with_compression(16);
+        let payload = Bytes::from("apples ".repeat(64));
+        let frame = Frame::new(Kind::Game, Flags::empty(), payload.clone());
+
+        let mut buffer = BytesMut::new();
+        codec.encode(frame, &mut buffer).expect("encode");
+        let header = Header::parse(&buffer).expect("header");
+        assert!(header.flags().contains(Flags::COMPRESSED));
+        assert!((header.payload_len() as usize) < payload.len());
+
+        let decoded = codec.decode(&mut buffer).expect("decode").expect("frame");
+        assert_eq!(decoded.payload(), &payload);
+        assert_eq!(decoded.header().flags(), Flags::empty());
+    }
+
+    #[test]
+    fn ignores_caller_compression_flag() {
+        let payload = Bytes::from("apples ".repeat(64));
+        for mut codec in [
+            FrameCodec::default(),
+            FrameCodec::default().with_compression(16),
+        ] {
+            let frame = Frame::new(Kind::Game, Flags::COMPRESSED, payload.clone());
+            let mut buffer = BytesMut::new();
+            codec.encode(frame, &mut buffer).expect("encode");
+            let decoded = codec.decode(&mut buffer).expect("decode").expect("frame");
+            assert_eq!(decoded.payload(), &payload);
+        }
+    }
+
+    #[test]
+    fn rejects_decompression_bombs() {
+        let payload = "a".repeat(4096);
+        let mut sender = FrameCodec::default().with_compression(0);
+        let mut receiver = FrameCodec::new(1024);
+
+        let mut buffer = BytesMut::new();
+        let frame = Frame::new(Kind::Game, Flags::empty(), Bytes::from(payload));
+        sender.encode(frame, &mut buffer).expect("encode");
+
+        assert!(matches!(
+            receiver.decode(&mut buffer),
+            Err(ProtoError::PayloadTooLarge {
+                len: 4096,
+                max: 1024
+            })
+        ));
+    }
+
+    #[test]
+    fn detects_corrupted_payloads() {
+        let mut codec = FrameCodec::default().with_checksum();
+        let payload = Bytes::from_static(b"Charming");
+        let frame = Frame::new(Kind::Game, Flags::empty(), payload.clone());
+
+        let mut buffer = BytesMut::new();
+        codec.encode(frame.clone(), &mut buffer).expect("encode");
+        assert_eq!(buffer.len(), Header::SIZE + payload.len() + CHECKSUM_SIZE);
+        let decoded = codec.decode(&mut buffer).expect("decode").expect("frame");
+        assert_eq!(decoded.payload(), &payload);
+        assert_eq!(decoded.header().flags(), Flags::empty());
+
+        codec.encode(frame, &mut buffer).expect("encode");
+        buffer[Header::SIZE] ^= 0xff;
+        assert!(matches!(
+            codec.decode(&mut buffer),
+            Err(ProtoError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn resyncs_past_garbage() {
+        let mut sender = FrameCodec::default().with_checksum();
+        let mut receiver = FrameCodec::default().with_resync();
+        let frame = |payload| Frame::new(Kind::Game, Flags::empty(), Bytes::from_static(payload));
+
+        let mut buffer = BytesMut::from(&b"noise"[..]);
+        sender
+            .encode(frame(b"Crispy"), &mut buffer)
+            .expect("encode");
+        buffer.extend_from_slice(b"A2A");
+        sender
+            .encode(frame(b"Bruised"), &mut buffer)
+            .expect("encode");
+        let corrupt = buffer.len() - 1;
+        buffer[corrupt] ^= 0xff;
+        sender
+            .encode(frame(b"Fragrant"), &mut buffer)
+            .expect("encode");
+
+        let first = receiver
+            .decode(&mut buffer)
+            .expect("decode")
+            .expect("frame");
+        assert_eq!(first.payload(), &b"Crispy"[..]);
+        let second = receiver
+            .decode(&mut buffer)
+            .expect("decode")
+            .expect("frame");
+        assert_eq!(second.payload(), &b"Fragrant"[..]);
+        assert!(buffer.is_empty());
+        assert_eq!(
+            *receiver.stats(),
+            DecoderStats {
+                frames: 2,
+                skipped_bytes: 8,
+                dropped_frames: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn fragments_oversized_messages() {
+        let mut codec = FrameCodec::new(8).with_fragmentation(64);
+        let payload = Bytes::from_static(b"Apples, apples, apples!");
+        let ping = Frame::new(Kind::Control, Flags::empty(), Bytes::from_static(b"ping"));
+
+        let mut buffer = BytesMut::new();
+        codec
+            .encode(
+                Frame::new(Kind::Game, Flags::PRIORITY, payload.clone()),
+                &mut buffer,
+            )
+            .expect("encode");
+        assert_eq!(buffer.len(), 3 * Header::SIZE + payload.len());
+        let mut control = BytesMut::new();
+        codec.encode(ping, &mut control).expect("encode");
+        let second = Header::SIZE + 8;
+        let tail = buffer.split_off(second);
+        buffer.unsplit(control);
+        buffer.unsplit(tail);
+
+        let control = codec.decode(&mut buffer).expect("decode").expect("frame");
+        assert_eq!(control.header().kind(), Kind::Control);
+        let decoded = codec.decode(&mut buffer).expect("decode").expect("frame");
+        assert_eq!(decoded.payload(), &payload);
+        assert_eq!(decoded.header().flags(), Flags::PRIORITY);
+        assert!(buffer.is_empty());
+
+        let too_long = Frame::new(Kind::Game, Flags::empty(), Bytes::from("a".repeat(65)));
+        assert!(matches!(
+            codec.encode(too_long, &mut buffer),
+            Err(ProtoError::MessageTooLarge { len: 65, max: 64 })
+        ));
+    }
+
+    #[test]
+    fn bounds_reassembly() {
+        let mut sender = FrameCodec::new(8).with_fragmentation(64);
+        let mut receiver = FrameCodec::new(8).with_fragmentation(16);
+
+        let mut buffer = BytesMut::new();
+        let frame = Frame::new(Kind::Game, Flags::empty(), Bytes::from("a".repeat(24)));
+        sender.encode(frame, &mut buffer).expect("encode");
+        assert!(matches!(
+            receiver.decode(&mut buffer),
+            Err(ProtoError::MessageTooLarge { len: 24, max: 16 })
+        ));
+
+        let mut buffer = BytesMut::new();
+        let frame = Frame::new(Kind::Game, Flags::empty(), Bytes::from("a".repeat(12)));
+        sender.encode(frame, &mut buffer).expect("encode");
+        buffer.truncate(Header::SIZE + 8);
+        let error = Frame::new(Kind::Error, Flags::empty(), Bytes::from_static(b"oops"));
+        sender.encode(error, &mut buffer).expect("encode");
+        assert!(matches!(
+            receiver.decode(&mut buffer),
+            Err(ProtoError::InterleavedFragment {
+                expected: Kind::Game,
+                found: Kind::Error
+            })
+        ));
+    }
+
+    #[test]
+    fn carries_correlation_ids() {
+        let mut codec = FrameCodec::new(8).with_fragmentation(64);
+        let payload = Bytes::from_static(b"Which one was it?");
+        let frame = Frame::new(Kind::Game, Flags::empty(), payload.clone())
+            .with_correlation(CorrelationId(7));
+
+        let mut buffer = BytesMut::new();
+        codec.encode(frame, &mut buffer).expect("encode");
+        let header = Header::parse(&buffer).expect("header");
+        assert!(header.flags().contains(Flags::CORRELATED));
+
+        let decoded = codec.decode(&mut buffer).expect("decode").expect("frame");
+        assert_eq!(decoded.correlation(), Some(CorrelationId(7)));
+        assert_eq!(decoded.payload(), &payload);
+        assert_eq!(decoded.header().flags(), Flags::empty());
+        assert_eq!(decoded.header().payload_len() as usize, payload.len());
+    }
+
+    #[test]
+    fn rejects_reserved_flags() {
+        let flags = Flags::COMPRESSED | Flags::PRIORITY;
+        let header = Header::new(Kind::Game, flags, 0);
+        assert_eq!(
+            Header::parse(header.as_bytes()).expect("parse").flags(),
+            flags
+        );
+
+        let mut bytes = header.as_bytes().to_vec();
+        bytes[7] |= 0x80;
+        assert_eq!(
+            Header::parse(&bytes),
+            Err(HeaderError::ReservedFlags(flags.bits() | 0x80))
+        );
+    }
+
+    #[test]
+    fn counts_traffic_and_header_errors() {
+        let sent = Arc::new(CodecMetrics::default());
+        let received = Arc::new(CodecMetrics::default());
+        let mut sender = FrameCodec::new(8)
+            .with_fragmentation(64)
+            .with_metrics(Arc::clone(&sent));
+        let mut receiver = FrameCodec::new(8)
+            .with_fragmentation(64)
+            .with_resync()
+            .with_metrics(Arc::clone(&received));
+        let game = Frame::new(Kind::Game, Flags::empty(), Bytes::from("a".repeat(12)));
+        let ping = Frame::new(Kind::Control, Flags::empty(), Bytes::from_static(b"ping"));
+
+        let mut buffer = BytesMut::new();
+        sender.encode(game, &mut buffer).expect("encode");
+        sender.encode(ping, &mut buffer).expect("encode");
+        let stale = Header::new(Kind::Game, Flags::empty(), 0).with_version(VERSION + 1);
+        buffer.extend_from_slice(stale.as_bytes());
+
+        assert!(receiver.decode(&mut buffer).expect("decode").is_some());
+        assert!(receiver.decode(&mut buffer).expect("decode").is_some());
+        assert!(receiver.decode(&mut buffer).expect("decode").is_none());
+
+        assert!(sender
+            .metrics()
+            .is_some_and(|metrics| Arc::ptr_eq(metrics, &sent)));
+        let sent = sent.snapshot();
+        let received = received.snapshot();
+        assert_eq!(sent.encodes, 2);
+        assert_eq!(sent.outbound(Kind::Game).frames, 2);
+        assert_eq!(
+            sent.outbound(Kind::Game).bytes,
+            2 * Header::SIZE as u64 + 12
+        );
+        assert_eq!(sent.outbound(Kind::Control).frames, 1);
+        assert_eq!(sent.max_frame_len, Header::SIZE as u64 + 8);
+        assert_eq!(received.inbound, sent.outbound);
+        assert_eq!(received.header_errors.unsupported_version, 1);
+        assert_eq!(received.header_errors.total(), 1);
+
+        let mut total = sent;
+        total += received;
+        assert_eq!(total.max_frame_len, sent.max_frame_len);
+        assert_eq!(total.inbound(Kind::Game).frames, 2);
+    }
+
+    #[test]
+    fn enforces_negotiated_version() {
+        let mut codec = FrameCodec::default();
+        let payload = Bytes::from_static(b"hello");
+        let header = |kind| {
+            let header =
+                Header::new(kind, Flags::empty(), payload.len() as u32).with_version(VERSION + 1);
+            let mut bytes = BytesMut::from(header.as_bytes());
+            bytes.extend_from_slice(&payload);
+            bytes
+        };
+
+        let hello = codec.decode(&mut header(Kind::Control)).expect("decode");
+        assert_eq!(hello.expect("frame").header().version(), VERSION + 1);
+        assert!(codec.decode(&mut header(Kind::Game)).is_err());
+
+        codec.set_version(VERSION);
+        assert!(matches!(
+            codec.decode(&mut header(Kind::Control)),
+            Err(ProtoError::Header(HeaderError::UnsupportedVersion { .. }))
+        ));
+    }
 }
@@ -1,4 +1,7 @@
-use thiserror::Error;
+use {
+    crate::{codec::CorrelationId, header::Kind},
+    thiserror::Error,
+};
 
 /// Parsing failure for the protocol header.
 #[derive(Debug, Error, Clone, PartialEq, Eq)]
@@ -13,6 +16,26 @@ pub enum HeaderError {
     UnsupportedVersion { found: u16, expected: u16 },
     #[error("unknown message kind {0}")]
     InvalidKind(u8),
+    #[error("reserved flag bits set: {0:#010b}")]
+    ReservedFlags(u8),
+}
+
+impl HeaderError {
+    /// Number of variants, for counters kept per variant.
+    pub const COUNT: usize = 6;
+
+    /// Position of the variant in declaration order, below `COUNT`.
+    #[inline]
+    pub const fn index(&self) -> usize {
+        match self {
+            Self::Truncated => 0,
+            Self::Misaligned => 1,
+            Self::InvalidMagic(_) => 2,
+            Self::UnsupportedVersion { .. } => 3,
+            Self::InvalidKind(_) => 4,
+            Self::ReservedFlags(_) => 5,
+        }
+    }
 }
 
 /// High-level protocol errors surfaced by the codec.
@@ -22,8 +45,51 @@ pub enum ProtoError {
     Header(#[from] HeaderError),
     #[error("payload length {len} exceeds maximum {max}")]
     PayloadTooLarge { len: u32, max: u32 },
+    #[error("message length {len} exceeds maximum {max}")]
+    MessageTooLarge { len: usize, max: u32 },
+    #[error("{found:?} frame interleaved with fragments of a {expected:?} message")]
+    InterleavedFragment { expected: Kind, found: Kind },
     #[error("payload length mismatch (header {expected}, actual {actual})")]
     LengthMismatch { expected: u32, actual: usize },
+    #[error("payload checksum mismatch (trailer {expected:#010x}, computed {found:#010x})")]
+    ChecksumMismatch { expected: u32, found: u32 },
+    #[error("correlated frame is too short to hold its correlation id")]
+    MissingCorrelation,
+    #[error("failed to decompress payload: {0}")]
+    Decompress(#[from] lz4_flex::block::DecompressError),
+    #[error("unsupported message version {found} (expected {expected})")]
+    UnsupportedMessageVersion { found: u16, expected: u16 },
+    #[error("message expects frame kind {expected:?}, found {found:?}")]
+    KindMismatch { expected: Kind, found: Kind },
+    #[error(
+        "no common protocol version (local {local_min}..={local_max}, remote {remote_min}..={remote_max})"
+    )]
+    NoCommonVersion {
+        local_min: u16,
+        local_max: u16,
+        remote_min: u16,
+        remote_max: u16,
+    },
+    #[error("connection closed during the handshake")]
+    HandshakeClosed,
+    #[error("unexpected handshake message")]
+    UnexpectedHandshake,
+    #[error("host chose a session the client did not offer")]
+    UnofferedSession,
+    #[error("no reply to request {0} in time")]
+    ReplyTimeout(CorrelationId),
+    #[error("request {0} was dropped before it got a reply")]
+    ReplyDropped(CorrelationId),
+    #[error(transparent)]
+    Payload(#[from] serde_json::Error),
+    #[error(transparent)]
+    MessagePackEncode(#[from] rmp_serde::encode::Error),
+    #[error(transparent)]
+    MessagePackDecode(#[from] rmp_serde::decode::Error),
+    #[error(transparent)]
+    BincodeEncode(#[from] bincode::error::EncodeError),
+    #[error(transparent)]
+    BincodeDecode(#[from] bincode::error::DecodeError),
     #[error(transparent)]
     Io(#[from] std::io::Error),
 }
@@ -0,0 +1,202 @@
+use {
+    crate::{
+        codec::{CorrelationId, Frame},
+        error::{HeaderError, ProtoError},
+        header::{Flags, Kind},
+        payload::{Json, PayloadCodec},
+    },
+    bytes::Bytes,
+    serde::{Deserialize, Serialize},
+    std::fmt,
+};
+
+/// Stable identifier of an error reported in a `Kind::Error` frame.
+///
+/// Codes are grouped by hundreds: 1xx framing, 2xx protocol, 3xx game and
+/// 5xx host failures. A released code never changes meaning; unknown codes
+/// from newer peers still decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ErrorCode(pub u16);
+
+impl ErrorCode {
+    pub const TRUNCATED_HEADER: Self = Self(100);
+    pub const INVALID_MAGIC: Self = Self(101);
+    pub const UNSUPPORTED_VERSION: Self = Self(102);
+    pub const INVALID_KIND: Self = Self(103);
+    pub const RESERVED_FLAGS: Self = Self(104);
+
+    pub const PAYLOAD_TOO_LARGE: Self = Self(200);
+    pub const MESSAGE_TOO_LARGE: Self = Self(201);
+    pub const LENGTH_MISMATCH: Self = Self(202);
+    pub const CHECKSUM_MISMATCH: Self = Self(203);
+    pub const DECOMPRESS: Self = Self(204);
+    pub const INTERLEAVED_FRAGMENT: Self = Self(205);
+    pub const MISSING_CORRELATION: Self = Self(206);
+    pub const UNSUPPORTED_MESSAGE_VERSION: Self = Self(207);
+    pub const KIND_MISMATCH: Self = Self(208);
+    pub const MALFORMED_PAYLOAD: Self = Self(209);
+    pub const HANDSHAKE_FAILED: Self = Self(210);
+    pub const TIMEOUT: Self = Self(211);
+    pub const IO: Self = Self(212);
+
+    pub const OUT_OF_CARDS: Self = Self(300);
+    pub const PLAYER_NOT_FOUND: Self = Self(301);
+    pub const ILLEGAL_MOVE: Self = Self(302);
+    pub const LOBBY_FULL: Self = Self(303);
+
+    pub const INTERNAL: Self = Self(500);
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "E{}", self.0)
+    }
+}
+
+/// Payload of a `Kind::Error` frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorFrame {
+    pub code: ErrorCode,
+    /// Human readable description, suitable for showing to the player.
+    pub message: String,
+    /// Whether repeating the request may succeed.
+    pub retryable: bool,
+    /// Request the error answers, if it answers one.
+    pub correlation: Option<CorrelationId>,
+}
+
+impl ErrorFrame {
+    #[inline]
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retryable: false,
+            correlation: None,
+        }
+    }
+
+    #[inline]
+    pub fn retryable(mut self) -> Self {
+        self.retryable = true;
+        self
+    }
+
+    #[inline]
+    pub fn with_correlation(mut self, id: Option<CorrelationId>) -> Self {
+        self.correlation = id;
+        self
+    }
+
+    /// Serializes the error as JSON into a `Kind::Error` frame carrying its correlation ID.
+    #[inline]
+    pub fn encode(&self) -> Result<Frame, ProtoError> {
+        self.encode_with(&Json)
+    }
+
+    pub fn encode_with(&self, codec: &impl PayloadCodec) -> Result<Frame, ProtoError> {
+        let payload = codec.encode(self)?;
+        let frame = Frame::new(Kind::Error, Flags::empty(), Bytes::from(payload));
+        Ok(match self.correlation {
+            Some(id) => frame.with_correlation(id),
+            None => frame,
+        })
+    }
+
+    #[inline]
+    pub fn decode(frame: &Frame) -> Result<Self, ProtoError> {
+        Self::decode_with(frame, &Json)
+    }
+
+    pub fn decode_with(frame: &Frame, codec: &impl PayloadCodec) -> Result<Self, ProtoError> {
+        let found = frame.header().kind();
+        if found != Kind::Error {
+            return Err(ProtoError::KindMismatch {
+                expected: Kind::Error,
+                found,
+            });
+        }
+        let mut error = codec.decode::<Self>(frame.payload())?;
+        error.correlation = error.correlation.or(frame.correlation());
+        Ok(error)
+    }
+}
+
+impl fmt::Display for ErrorFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+impl From<&HeaderError> for ErrorFrame {
+    fn from(error: &HeaderError) -> Self {
+        let code = match error {
+            HeaderError::Truncated | HeaderError::Misaligned => ErrorCode::TRUNCATED_HEADER,
+            HeaderError::InvalidMagic(_) => ErrorCode::INVALID_MAGIC,
+            HeaderError::UnsupportedVersion { .. } => ErrorCode::UNSUPPORTED_VERSION,
+            HeaderError::InvalidKind(_) => ErrorCode::INVALID_KIND,
+            HeaderError::ReservedFlags(_) => ErrorCode::RESERVED_FLAGS,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+impl From<&ProtoError> for ErrorFrame {
+    fn from(error: &ProtoError) -> Self {
+        let code = match error {
+            ProtoError::Header(error) => return error.into(),
+            ProtoError::PayloadTooLarge { .. } => ErrorCode::PAYLOAD_TOO_LARGE,
+            ProtoError::MessageTooLarge { .. } => ErrorCode::MESSAGE_TOO_LARGE,
+            ProtoError::InterleavedFragment { .. } => ErrorCode::INTERLEAVED_FRAGMENT,
+            ProtoError::LengthMismatch { .. } => ErrorCode::LENGTH_MISMATCH,
+            ProtoError::ChecksumMismatch { .. } => ErrorCode::CHECKSUM_MISMATCH,
+            ProtoError::MissingCorrelation => ErrorCode::MISSING_CORRELATION,
+            ProtoError::Decompress(_) => ErrorCode::DECOMPRESS,
+            ProtoError::UnsupportedMessageVersion { .. } => ErrorCode::UNSUPPORTED_MESSAGE_VERSION,
+            ProtoError::KindMismatch { .. } => ErrorCode::KIND_MISMATCH,
+            ProtoError::NoCommonVersion { .. }
+            | ProtoError::HandshakeClosed
+            | ProtoError::UnexpectedHandshake
+            | ProtoError::UnofferedSession => ErrorCode::HANDSHAKE_FAILED,
+            ProtoError::ReplyTimeout(_) | ProtoError::ReplyDropped(_) => {
+                return Self::new(ErrorCode::TIMEOUT, error.to_string()).retryable();
+            }
+            ProtoError::Payload(_)
+            | ProtoError::MessagePackEncode(_)
+            | ProtoError::MessagePackDecode(_)
+            | ProtoError::BincodeEncode(_)
+            | ProtoError::BincodeDecode(_) => ErrorCode::MALFORMED_PAYLOAD,
+            ProtoError::Io(_) => {
+                return Self::new(ErrorCode::IO, error.to_string()).retryable();
+            }
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{ErrorCode, ErrorFrame},
+        crate::{codec::CorrelationId, error::ProtoError, header::Kind},
+    };
+
+    #[test]
+    fn roundtrips_with_correlation() {
+        let error = ErrorFrame::from(&ProtoError::ReplyTimeout(CorrelationId(3)))
+            .with_correlation(Some(CorrelationId(3)));
+        assert_eq!(error.code, ErrorCode::TIMEOUT);
+        assert!(error.retryable);
+
+        let frame = error.encode().expect("encode");
+        assert_eq!(frame.header().kind(), Kind::Error);
+        assert_eq!(frame.correlation(), Some(CorrelationId(3)));
+        assert_eq!(ErrorFrame::decode(&frame).expect("decode"), error);
+
+        let json =
+            br#"{"code":999,"message":"from the future","retryable":false,"correlation":null}"#;
+        let future: ErrorFrame = serde_json::from_slice(json).expect("unknown code");
+        assert_eq!(future.code, ErrorCode(999));
+    }
+}
@@ -0,0 +1,298 @@
+use {
+    crate::{
+        codec::{Frame, FrameCodec, COMPRESSION_THRESHOLD, MAX_MESSAGE_LEN},
+        error::ProtoError,
+        header::{Flags, Kind, MIN_VERSION, VERSION},
+        payload::PayloadFormat,
+    },
+    bitflags::bitflags,
+    bytes::Bytes,
+    futures_util::{SinkExt, StreamExt},
+    serde::{Deserialize, Deserializer, Serialize, Serializer},
+    tokio::io::{AsyncRead, AsyncWrite},
+    tokio_util::codec::Framed,
+};
+
+bitflags! {
+    /// Optional protocol features a peer advertises during the handshake.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+    pub struct Features: u32 {
+        /// Large payloads may be sent compressed.
+        const COMPRESSION = 1 << 0;
+        /// Payloads carry a CRC32C trailer.
+        const CHECKSUM = 1 << 1;
+        /// Messages too large for one frame are split into fragments.
+        const FRAGMENTATION = 1 << 2;
+    }
+}
+
+impl Serialize for Features {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.bits())
+    }
+}
+
+impl<'de> Deserialize<'de> for Features {
+    /// Bits this build does not know about are dropped.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u32::deserialize(deserializer).map(Self::from_bits_truncate)
+    }
+}
+
+/// Versions and features a peer supports, sent as the first frame of a session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hello {
+    pub min_version: u16,
+    pub max_version: u16,
+    pub features: Features,
+    /// Payload formats the peer speaks, preferred first.
+    #[serde(default = "json_only")]
+    pub formats: Vec<PayloadFormat>,
+}
+
+fn json_only() -> Vec<PayloadFormat> {
+    vec![PayloadFormat::Json]
+}
+
+impl Default for Hello {
+    /// Everything this build supports.
+    #[inline]
+    fn default() -> Self {
+        Self {
+            min_version: MIN_VERSION,
+            max_version: VERSION,
+            features: Features::all(),
+            formats: PayloadFormat::ALL.to_vec(),
+        }
+    }
+}
+
+impl Hello {
+    /// Moves `format` to the front of the advertised formats.
+    pub fn prefer(mut self, format: PayloadFormat) -> Self {
+        self.formats.retain(|&other| other != format);
+        self.formats.insert(0, format);
+        self
+    }
+
+    /// Highest version both sides speak, the features both support and the
+    /// remote's most preferred format we also speak, falling back to JSON.
+    pub fn negotiate(&self, remote: &Hello) -> Option<Session> {
+        let version = self.max_version.min(remote.max_version);
+        let format = remote
+            .formats
+            .iter()
+            .copied()
+            .find(|format| self.formats.contains(format))
+            .unwrap_or_default();
+        (version >= self.min_version.max(remote.min_version)).then(|| Session {
+            version,
+            features: self.features & remote.features,
+            format,
+        })
+    }
+
+    /// Whether `session` only uses a version, features and format this hello offered.
+    pub fn offers(&self, session: &Session) -> bool {
+        (self.min_version..=self.max_version).contains(&session.version)
+            && self.features.contains(session.features)
+            && self.formats.contains(&session.format)
+    }
+
+    #[inline]
+    fn no_common_version(&self, remote: &Hello) -> ProtoError {
+        ProtoError::NoCommonVersion {
+            local_min: self.min_version,
+            local_max: self.max_version,
+            remote_min: remote.min_version,
+            remote_max: remote.max_version,
+        }
+    }
+}
+
+/// Parameters both peers settled on for the rest of the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub version: u16,
+    pub features: Features,
+    /// Format every message payload is encoded with.
+    #[serde(default)]
+    pub format: PayloadFormat,
+}
+
+impl Session {
+    /// Configures the codec for the rest of the session.
+    pub fn apply(&self, codec: &mut FrameCodec) {
+        codec.set_version(self.version);
+        codec.set_compression(
+            self.features
+                .contains(Features::COMPRESSION)
+                .then_some(COMPRESSION_THRESHOLD),
+        );
+        codec.set_checksum(self.features.contains(Features::CHECKSUM));
+        codec.set_fragmentation(
+            self.features
+                .contains(Features::FRAGMENTATION)
+                .then_some(MAX_MESSAGE_LEN),
+        );
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub(crate) enum Handshake {
+    Hello(Hello),
+    Accept(Session),
+    /// The host shares its own range when there is no overlap.
+    Reject(Hello),
+}
+
+impl Handshake {
+    fn encode(&self) -> Result<Frame, ProtoError> {
+        let payload = serde_json::to_vec(self)?;
+        Ok(Frame::new(
+            Kind::Control,
+            Flags::empty(),
+            Bytes::from(payload),
+        ))
+    }
+
+    async fn receive<T>(framed: &mut Framed<T, FrameCodec>) -> Result<Self, ProtoError>
+    where
+        T: AsyncRead + AsyncWrite + Unpin,
+    {
+        let frame = framed
+            .next()
+            .await
+            .transpose()?
+            .ok_or(ProtoError::HandshakeClosed)?;
+        if frame.header().kind() != Kind::Control {
+            return Err(ProtoError::UnexpectedHandshake);
+        }
+        Ok(serde_json::from_slice(frame.payload())?)
+    }
+}
+
+/// Client side: advertise `hello` and configure the codec for the session the host picks.
+pub async fn initiate<T>(
+    framed: &mut Framed<T, FrameCodec>,
+    hello: Hello,
+) -> Result<Session, ProtoError>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    framed
+        .send(Handshake::Hello(hello.clone()).encode()?)
+        .await?;
+    match Handshake::receive(framed).await? {
+        Handshake::Accept(session) if hello.offers(&session) => {
+            session.apply(framed.codec_mut());
+            Ok(session)
+        }
+        Handshake::Accept(_) => Err(ProtoError::UnofferedSession),
+        Handshake::Reject(remote) => Err(hello.no_common_version(&remote)),
+        _ => Err(ProtoError::UnexpectedHandshake),
+    }
+}
+
+/// Host side: answer the peer's hello with the highest common version and configure the codec.
+pub async fn respond<T>(
+    framed: &mut Framed<T, FrameCodec>,
+    hello: Hello,
+) -> Result<Session, ProtoError>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    let Handshake::Hello(remote) = Handshake::receive(framed).await? else {
+        return Err(ProtoError::UnexpectedHandshake);
+    };
+    let Some(session) = hello.negotiate(&remote) else {
+        framed
+            .send(Handshake::Reject(hello.clone()).encode()?)
+            .await?;
+        return Err(hello.no_common_version(&remote));
+    };
+
+    framed.send(Handshake::Accept(session).encode()?).await?;
+    session.apply(framed.codec_mut());
+    Ok(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{Features, Hello, Session},
+        crate::{
+            codec::{Frame, FrameCodec, COMPRESSION_THRESHOLD},
+            header::{Flags, Header, Kind},
+            payload::PayloadFormat,
+        },
+        bytes::{Bytes, BytesMut},
+        tokio_util::codec::Encoder,
+    };
+
+    #[test]
+    fn picks_highest_common_version() {
+        let local = Hello {
+            min_version: 1,
+            max_version: 3,
+            features: Features::all(),
+            formats: PayloadFormat::ALL.to_vec(),
+        };
+        let remote = Hello {
+            min_version: 2,
+            max_version: 5,
+            features: Features::empty(),
+            formats: vec![PayloadFormat::Json],
+        };
+
+        let session = local.negotiate(&remote).expect("ranges overlap");
+        assert_eq!(session.version, 3);
+        assert_eq!(session.features, Features::empty());
+        assert_eq!(session.format, PayloadFormat::Json);
+
+        let newer = Hello {
+            min_version: 4,
+            ..remote
+        };
+        assert_eq!(local.negotiate(&newer), None);
+    }
+
+    #[test]
+    fn remote_preference_picks_the_format() {
+        let host = Hello::default();
+        let client = Hello::default().prefer(PayloadFormat::MessagePack);
+        let session = host.negotiate(&client).expect("same build");
+        assert_eq!(session.format, PayloadFormat::MessagePack);
+
+        let legacy = Hello {
+            formats: vec![PayloadFormat::Bincode],
+            ..Hello::default()
+        };
+        let json_host = Hello {
+            formats: vec![PayloadFormat::Json],
+            ..Hello::default()
+        };
+        let session = json_host.negotiate(&legacy).expect("same versions");
+        assert_eq!(session.format, PayloadFormat::Json);
+    }
+
+    #[test]
+    fn apply_turns_compression_off_too() {
+        let mut codec = FrameCodec::default().with_compression(0);
+        let session = Session {
+            version: 1,
+            features: Features::empty(),
+            format: PayloadFormat::Json,
+        };
+        session.apply(&mut codec);
+
+        let payload = Bytes::from("apples ".repeat(COMPRESSION_THRESHOLD));
+        let mut buffer = BytesMut::new();
+        codec
+            .encode(Frame::new(Kind::Game, Flags::empty(), payload), &mut buffer)
+            .expect("encode");
+        let header = Header::parse(&buffer).expect("header");
+        assert!(!header.flags().contains(Flags::COMPRESSED));
+    }
+}
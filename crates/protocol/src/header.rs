@@ -1,12 +1,16 @@
 use {
-    crate::error::HeaderError,
+    crate::{codec::CHECKSUM_SIZE, error::HeaderError},
+    bitflags::bitflags,
     zerocopy::{error::CastError, IntoBytes, Ref},
     zerocopy_derive::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned},
 };
 
 /// Protocol wire-format constants.
 pub const MAGIC: [u8; 4] = *b"A2A!";
+/// Newest wire version this build speaks.
 pub const VERSION: u16 = 1;
+/// Oldest wire version this build still speaks.
+pub const MIN_VERSION: u16 = 1;
 
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -37,6 +41,32 @@ impl From<Kind> for u8 {
     }
 }
 
+bitflags! {
+    /// Per-frame options carried in the header's flags byte.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Flags: u8 {
+        /// The payload is compressed.
+        const COMPRESSED = 1 << 0;
+        /// The sender expects the frame to be acknowledged.
+        const REQUIRES_ACK = 1 << 1;
+        /// Last fragment of a message split across several frames.
+        const FINAL_FRAGMENT = 1 << 2;
+        /// The payload is encrypted.
+        const ENCRYPTED = 1 << 3;
+        /// The frame should be handled ahead of regular traffic.
+        const PRIORITY = 1 << 4;
+        /// A CRC32C of the payload follows it on the wire.
+        const CHECKSUM = 1 << 5;
+        /// A correlation ID prefixes the payload on the wire.
+        const CORRELATED = 1 << 6;
+    }
+}
+
+impl Flags {
+    /// Bits without a defined meaning; frames carrying them are rejected.
+    pub const RESERVED: u8 = !Self::all().bits();
+}
+
 #[repr(C)]
 #[derive(
     FromBytes,
@@ -62,12 +92,12 @@ impl RawHeader {
     const SIZE: usize = core::mem::size_of::<Self>();
 
     #[inline]
-    fn new(kind: Kind, flags: u8, payload_len: u32) -> Self {
+    fn new(kind: Kind, flags: Flags, payload_len: u32) -> Self {
         Self {
             magic: MAGIC,
             version: VERSION.to_be_bytes(),
             kind: kind.into(),
-            flags,
+            flags: flags.bits(),
             payload_len: payload_len.to_be_bytes(),
         }
     }
@@ -93,7 +123,7 @@ impl Header {
     pub const SIZE: usize = RawHeader::SIZE;
 
     #[inline]
-    pub fn new(kind: Kind, flags: u8, payload_len: u32) -> Self {
+    pub fn new(kind: Kind, flags: Flags, payload_len: u32) -> Self {
         Self { raw: RawHeader::new(kind, flags, payload_len) }
     }
 
@@ -103,8 +133,8 @@ impl Header {
     }
 
     #[inline]
-    pub fn flags(&self) -> u8 {
-        self.raw.flags
+    pub fn flags(&self) -> Flags {
+        Flags::from_bits_retain(self.raw.flags)
     }
 
     #[inline]
@@ -112,14 +142,31 @@ impl Header {
         self.raw.payload_len()
     }
 
+    /// Bytes the whole frame takes on the wire, checksum trailer included.
+    #[inline]
+    pub fn frame_len(&self) -> usize {
+        let trailer = if self.flags().contains(Flags::CHECKSUM) {
+            CHECKSUM_SIZE
+        } else {
+            0
+        };
+        Self::SIZE + self.payload_len() as usize + trailer
+    }
+
     #[inline]
     pub fn version(&self) -> u16 {
         self.raw.version()
     }
 
     #[inline]
-    pub fn with_flags(mut self, flags: u8) -> Self {
-        self.raw.flags = flags;
+    pub fn with_version(mut self, version: u16) -> Self {
+        self.raw.version = version.to_be_bytes();
+        self
+    }
+
+    #[inline]
+    pub fn with_flags(mut self, flags: Flags) -> Self {
+        self.raw.flags = flags.bits();
         self
     }
 
@@ -134,6 +181,8 @@ impl Header {
         self.raw.as_bytes()
     }
 
+    /// Validates magic and kind; the version is checked by the codec against
+    /// the one negotiated for the session.
     pub fn parse(bytes: &[u8]) -> Result<Self, HeaderError> {
         if bytes.len() < Self::SIZE {
             return Err(HeaderError::Truncated);
@@ -147,15 +196,12 @@ impl Header {
             return Err(HeaderError::InvalidMagic(raw.magic));
         }
 
-        if raw.version() != VERSION {
-            return Err(HeaderError::UnsupportedVersion {
-                found: raw.version(),
-                expected: VERSION,
-            });
-        }
-
         Kind::try_from(raw.kind)?;
 
+        if raw.flags & Flags::RESERVED != 0 {
+            return Err(HeaderError::ReservedFlags(raw.flags));
+        }
+
         Ok(Self { raw })
     }
 }
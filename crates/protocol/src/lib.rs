@@ -1,7 +1,21 @@
+pub mod capture;
 pub mod codec;
 pub mod error;
+pub mod error_frame;
+pub mod handshake;
 pub mod header;
+pub mod message;
+pub mod metrics;
+pub mod payload;
+pub mod tracker;
 
-pub use codec::{Frame, FrameCodec};
+pub use capture::{Capture, CaptureError, CaptureReader, Direction, Inspector, Record};
+pub use codec::{CorrelationId, DecoderStats, Frame, FrameCodec};
 pub use error::{HeaderError, ProtoError};
-pub use header::{Header, Kind, MAGIC, VERSION};
+pub use error_frame::{ErrorCode, ErrorFrame};
+pub use handshake::{Features, Hello, Session};
+pub use header::{Flags, Header, Kind, MAGIC, MIN_VERSION, VERSION};
+pub use message::{Message, MESSAGE_VERSION};
+pub use metrics::{CodecMetrics, HeaderErrors, Metrics, Traffic};
+pub use payload::{PayloadCodec, PayloadFormat};
+pub use tracker::{ReplyPort, RequestTracker};
@@ -0,0 +1,341 @@
+use {
+    crate::{
+        codec::Frame,
+        error::ProtoError,
+        error_frame::ErrorFrame,
+        header::{Flags, Kind},
+        payload::{Json, PayloadCodec},
+    },
+    apples_core::{cards::card::CardId, GreenCard, PlayerId, RedCard},
+    bytes::Bytes,
+    serde::{Deserialize, Serialize},
+};
+
+/// Version of the message schema carried inside frame payloads.
+pub const MESSAGE_VERSION: u16 = 1;
+
+/// Typed game message exchanged between host and clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum Message {
+    /// Client asks to join the lobby under the given display name.
+    JoinRequest { name: String },
+    /// Host accepts a join request and assigns the player an id.
+    Welcome { player_id: PlayerId },
+    /// Either side checks that the other is still there.
+    Ping,
+    /// Answer to a `Ping`.
+    Pong,
+    /// Host deals red cards that are added to the player's hand.
+    DealHand { cards: Vec<RedCard> },
+    /// Host discards the player's hand and replaces it with freshly drawn cards.
+    ReplaceHand { cards: Vec<RedCard> },
+    /// Host reveals the green card for the round and who is judging it.
+    RevealGreen { judge: PlayerId, card: GreenCard },
+    /// Host reveals two green cards that a single red card is judged against.
+    RevealGreenPair {
+        judge: PlayerId,
+        first: GreenCard,
+        second: GreenCard,
+    },
+    /// Host asks the player to play `count` red cards from their hand.
+    PlayRequest { count: usize },
+    /// Player submits red cards from their hand.
+    SubmitRed { cards: Vec<RedCard> },
+    /// Host accepted a `SubmitRed`; the cards have left the player's hand.
+    Submitted { cards: Vec<RedCard> },
+    /// Host hands the anonymised submissions to a player picking among them;
+    /// `worst` asks for the worst match instead of the best.
+    JudgeRequest {
+        submissions: Vec<Vec<RedCard>>,
+        #[serde(default)]
+        worst: bool,
+    },
+    /// Player picks a submission, identified by any of its red cards.
+    JudgeChoice { card: CardId },
+    /// Host announces who won the round, the green cards they take and the
+    /// red cards that decided it; nobody and none for a voided round.
+    RoundResult {
+        winners: Vec<PlayerId>,
+        greens: Vec<GreenCard>,
+        reds: Vec<RedCard>,
+    },
+    /// Host broadcasts the current green card tally per player.
+    ScoreUpdate { scores: Vec<(PlayerId, usize)> },
+    /// Host announces the winner of the game.
+    GameOver { winner: PlayerId },
+    /// Either side reports a failed request; travels as a bare `ErrorFrame`
+    /// in a `Kind::Error` frame. A player answering a `PlayRequest` or
+    /// `JudgeRequest` with one passes for the round.
+    Error(ErrorFrame),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope<M> {
+    version: u16,
+    message: M,
+}
+
+impl Message {
+    /// Frame kind the message travels under.
+    #[inline]
+    pub fn kind(&self) -> Kind {
+        match self {
+            Self::JoinRequest { .. } | Self::Welcome { .. } | Self::Ping | Self::Pong => {
+                Kind::Control
+            }
+            Self::Error(_) => Kind::Error,
+            _ => Kind::Game,
+        }
+    }
+
+    /// Serializes the message as JSON into a frame of the matching kind.
+    #[inline]
+    pub fn encode(&self) -> Result<Frame, ProtoError> {
+        self.encode_with(&Json)
+    }
+
+    /// Serializes the message with `codec` into a frame of the matching kind.
+    pub fn encode_with(&self, codec: &impl PayloadCodec) -> Result<Frame, ProtoError> {
+        if let Self::Error(error) = self {
+            return error.encode_with(codec);
+        }
+        let payload = if codec.self_describing() {
+            codec.encode(&Envelope {
+                version: MESSAGE_VERSION,
+                message: self,
+            })?
+        } else {
+            codec.encode(&Envelope {
+                version: MESSAGE_VERSION,
+                message: compact::Ref(self),
+            })?
+        };
+        Ok(Frame::new(
+            self.kind(),
+            Flags::empty(),
+            Bytes::from(payload),
+        ))
+    }
+
+    /// Deserializes a JSON message from a frame, checking the schema version and frame kind.
+    #[inline]
+    pub fn decode(frame: &Frame) -> Result<Self, ProtoError> {
+        Self::decode_with(frame, &Json)
+    }
+
+    /// Deserializes a message encoded with `codec`, checking the schema version and frame kind.
+    pub fn decode_with(frame: &Frame, codec: &impl PayloadCodec) -> Result<Self, ProtoError> {
+        if frame.header().kind() == Kind::Error {
+            return ErrorFrame::decode_with(frame, codec).map(Self::Error);
+        }
+        let (version, message) = if codec.self_describing() {
+            let Envelope { version, message } = codec.decode::<Envelope<Self>>(frame.payload())?;
+            (version, message)
+        } else {
+            let Envelope {
+                version,
+                message: compact::Owned(message),
+            } = codec.decode(frame.payload())?;
+            (version, message)
+        };
+
+        if version != MESSAGE_VERSION {
+            return Err(ProtoError::UnsupportedMessageVersion {
+                found: version,
+                expected: MESSAGE_VERSION,
+            });
+        }
+
+        let found = frame.header().kind();
+        if message.kind() != found {
+            return Err(ProtoError::KindMismatch {
+                expected: message.kind(),
+                found,
+            });
+        }
+
+        Ok(message)
+    }
+}
+
+impl TryFrom<&Frame> for Message {
+    type Error = ProtoError;
+
+    #[inline]
+    fn try_from(frame: &Frame) -> Result<Self, ProtoError> {
+        Self::decode(frame)
+    }
+}
+
+/// Externally tagged twin of `Message` for formats that cannot read the
+/// adjacently tagged JSON layout. `serde(remote)` keeps the two in lockstep.
+mod compact {
+    use {
+        super::Message,
+        crate::error_frame::ErrorFrame,
+        apples_core::{cards::card::CardId, GreenCard, PlayerId, RedCard},
+        serde::{Deserialize, Deserializer, Serialize, Serializer},
+    };
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "Message")]
+    enum MessageDef {
+        JoinRequest {
+            name: String,
+        },
+        Welcome {
+            player_id: PlayerId,
+        },
+        Ping,
+        Pong,
+        DealHand {
+            cards: Vec<RedCard>,
+        },
+        ReplaceHand {
+            cards: Vec<RedCard>,
+        },
+        RevealGreen {
+            judge: PlayerId,
+            card: GreenCard,
+        },
+        RevealGreenPair {
+            judge: PlayerId,
+            first: GreenCard,
+            second: GreenCard,
+        },
+        PlayRequest {
+            count: usize,
+        },
+        SubmitRed {
+            cards: Vec<RedCard>,
+        },
+        Submitted {
+            cards: Vec<RedCard>,
+        },
+        JudgeRequest {
+            submissions: Vec<Vec<RedCard>>,
+            worst: bool,
+        },
+        JudgeChoice {
+            card: CardId,
+        },
+        RoundResult {
+            winners: Vec<PlayerId>,
+            greens: Vec<GreenCard>,
+            reds: Vec<RedCard>,
+        },
+        ScoreUpdate {
+            scores: Vec<(PlayerId, usize)>,
+        },
+        GameOver {
+            winner: PlayerId,
+        },
+        Error(ErrorFrame),
+    }
+
+    pub(super) struct Ref<'a>(pub &'a Message);
+
+    impl Serialize for Ref<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            MessageDef::serialize(self.0, serializer)
+        }
+    }
+
+    pub(super) struct Owned(pub Message);
+
+    impl<'de> Deserialize<'de> for Owned {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            MessageDef::deserialize(deserializer).map(Owned)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::Message,
+        crate::{
+            codec::{Frame, FrameCodec},
+            error::ProtoError,
+            error_frame::{ErrorCode, ErrorFrame},
+            header::{Flags, Kind},
+            payload::PayloadFormat,
+        },
+        apples_core::{GreenCard, PlayerId, RedCard},
+        bytes::BytesMut,
+        tokio_util::codec::{Decoder, Encoder},
+    };
+
+    #[test]
+    fn roundtrip_through_codec() {
+        let mut codec = FrameCodec::default();
+        let message = Message::SubmitRed {
+            cards: vec![RedCard::new(
+                3usize,
+                "Fresh Socks",
+                "The warm comfort of dryer-fresh socks",
+            )],
+        };
+
+        let frame = message.encode().expect("encode message");
+        assert_eq!(frame.header().kind(), Kind::Game);
+
+        let mut buffer = BytesMut::new();
+        codec.encode(frame, &mut buffer).expect("encode frame");
+        let decoded = codec.decode(&mut buffer).expect("decode").expect("frame");
+
+        assert_eq!(Message::decode(&decoded).expect("decode message"), message);
+    }
+
+    #[test]
+    fn roundtrips_in_every_format() {
+        let red = RedCard::new(
+            3usize,
+            "Fresh Socks",
+            "The warm comfort of dryer-fresh socks",
+        );
+        let messages = [
+            Message::Ping,
+            Message::JoinRequest {
+                name: String::from("Ada"),
+            },
+            Message::JudgeRequest {
+                submissions: vec![vec![red.clone()]],
+                worst: true,
+            },
+            Message::RoundResult {
+                winners: vec![PlayerId(2)],
+                greens: vec![GreenCard::new(1usize, "Cozy", "Snug, comfortable.")],
+                reds: vec![red],
+            },
+            Message::Error(ErrorFrame::new(ErrorCode::ILLEGAL_MOVE, "Not your turn")),
+        ];
+
+        for format in PayloadFormat::ALL {
+            for message in &messages {
+                let frame = message.encode_with(&format).expect("encode message");
+                let decoded = Message::decode_with(&frame, &format).expect("decode message");
+                assert_eq!(&decoded, message, "{format:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_kind_mismatch() {
+        let frame = Message::Welcome {
+            player_id: PlayerId(1),
+        }
+        .encode()
+        .expect("encode message");
+        let forged = Frame::new(Kind::Game, Flags::empty(), frame.into_payload());
+
+        assert!(matches!(
+            Message::decode(&forged),
+            Err(ProtoError::KindMismatch {
+                expected: Kind::Control,
+                found: Kind::Game,
+            })
+        ));
+    }
+}
@@ -0,0 +1,257 @@
+use {
+    crate::{error::HeaderError, header::Kind},
+    std::{
+        fmt,
+        ops::AddAssign,
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    },
+};
+
+const KINDS: usize = 3;
+
+/// Frames and wire bytes of one `Kind` in one direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Traffic {
+    pub frames: u64,
+    pub bytes: u64,
+}
+
+/// Header errors the decoder ran into, by `HeaderError` variant.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeaderErrors {
+    pub truncated: u64,
+    pub misaligned: u64,
+    pub invalid_magic: u64,
+    pub unsupported_version: u64,
+    pub invalid_kind: u64,
+    pub reserved_flags: u64,
+}
+
+impl HeaderErrors {
+    #[inline]
+    pub fn total(&self) -> u64 {
+        self.counts().iter().sum()
+    }
+
+    /// The counters indexed by `HeaderError::index`.
+    pub fn counts(&self) -> [u64; HeaderError::COUNT] {
+        [
+            self.truncated,
+            self.misaligned,
+            self.invalid_magic,
+            self.unsupported_version,
+            self.invalid_kind,
+            self.reserved_flags,
+        ]
+    }
+}
+
+/// Takes counters indexed by `HeaderError::index`.
+impl From<[u64; HeaderError::COUNT]> for HeaderErrors {
+    fn from(counts: [u64; HeaderError::COUNT]) -> Self {
+        let [truncated, misaligned, invalid_magic, unsupported_version, invalid_kind, reserved_flags] =
+            counts;
+        Self {
+            truncated,
+            misaligned,
+            invalid_magic,
+            unsupported_version,
+            invalid_kind,
+            reserved_flags,
+        }
+    }
+}
+
+/// Point-in-time copy of a codec's counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Metrics {
+    /// Decoded frames, indexed by `Kind as usize`.
+    pub inbound: [Traffic; KINDS],
+    /// Encoded frames, indexed by `Kind as usize`.
+    pub outbound: [Traffic; KINDS],
+    pub header_errors: HeaderErrors,
+    /// Largest frame seen on the wire in either direction, header included.
+    pub max_frame_len: u64,
+    pub encodes: u64,
+    /// Time spent encoding, summed over all `encodes`.
+    pub encode_time: Duration,
+}
+
+impl Metrics {
+    #[inline]
+    pub fn inbound(&self, kind: Kind) -> Traffic {
+        self.inbound[kind as usize]
+    }
+
+    #[inline]
+    pub fn outbound(&self, kind: Kind) -> Traffic {
+        self.outbound[kind as usize]
+    }
+
+    /// Mean time one `encode` call took.
+    pub fn mean_encode_time(&self) -> Duration {
+        match u32::try_from(self.encodes) {
+            Ok(0) => Duration::ZERO,
+            Ok(encodes) => self.encode_time / encodes,
+            Err(_) => {
+                Duration::from_nanos((self.encode_time.as_nanos() / self.encodes as u128) as u64)
+            }
+        }
+    }
+}
+
+/// Sums counters, for totals across connections.
+impl AddAssign for Metrics {
+    fn add_assign(&mut self, other: Self) {
+        for (mine, theirs) in self
+            .inbound
+            .iter_mut()
+            .chain(self.outbound.iter_mut())
+            .zip(other.inbound.iter().chain(other.outbound.iter()))
+        {
+            mine.frames += theirs.frames;
+            mine.bytes += theirs.bytes;
+        }
+        let mut errors = self.header_errors.counts();
+        for (mine, theirs) in errors.iter_mut().zip(other.header_errors.counts()) {
+            *mine += theirs;
+        }
+        self.header_errors = errors.into();
+        self.max_frame_len = self.max_frame_len.max(other.max_frame_len);
+        self.encodes += other.encodes;
+        self.encode_time += other.encode_time;
+    }
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sum = |traffic: &[Traffic; KINDS]| {
+            traffic.iter().fold((0, 0), |(frames, bytes), kind| {
+                (frames + kind.frames, bytes + kind.bytes)
+            })
+        };
+        let (frames_in, bytes_in) = sum(&self.inbound);
+        let (frames_out, bytes_out) = sum(&self.outbound);
+        write!(
+            f,
+            "in {frames_in} frames/{bytes_in} B, out {frames_out} frames/{bytes_out} B, \
+             {} header errors, largest frame {} B, mean encode {:?}",
+            self.header_errors.total(),
+            self.max_frame_len,
+            self.mean_encode_time()
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counter {
+    frames: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl Counter {
+    fn add(&self, bytes: usize) {
+        self.frames.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn load(&self) -> Traffic {
+        Traffic {
+            frames: self.frames.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Live counters a `FrameCodec` updates as it works.
+///
+/// Counts the frames and wire bytes decoded and encoded per `Kind`, the
+/// header errors the decoder runs into by `HeaderError` variant, the largest
+/// frame seen and the time spent encoding. Attach them with
+/// `FrameCodec::with_metrics` and read them with `snapshot`; they are shared
+/// behind an `Arc`, so they stay readable after the `Framed` around the codec
+/// was split into halves.
+#[derive(Debug, Default)]
+pub struct CodecMetrics {
+    inbound: [Counter; KINDS],
+    outbound: [Counter; KINDS],
+    header_errors: [AtomicU64; HeaderError::COUNT],
+    max_frame_len: AtomicU64,
+    encodes: AtomicU64,
+    encode_nanos: AtomicU64,
+}
+
+impl CodecMetrics {
+    pub(crate) fn frame_in(&self, kind: Kind, len: usize) {
+        self.inbound[kind as usize].add(len);
+        self.max_frame_len.fetch_max(len as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn frame_out(&self, kind: Kind, len: usize) {
+        self.outbound[kind as usize].add(len);
+        self.max_frame_len.fetch_max(len as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn header_error(&self, error: &HeaderError) {
+        self.header_errors[error.index()].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn encoded(&self, took: Duration) {
+        self.encodes.fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(took.as_nanos()).unwrap_or(u64::MAX);
+        self.encode_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// Copies the counters as they are now.
+    pub fn snapshot(&self) -> Metrics {
+        Metrics {
+            inbound: self.inbound.each_ref().map(Counter::load),
+            outbound: self.outbound.each_ref().map(Counter::load),
+            header_errors: self
+                .header_errors
+                .each_ref()
+                .map(|count| count.load(Ordering::Relaxed))
+                .into(),
+            max_frame_len: self.max_frame_len.load(Ordering::Relaxed),
+            encodes: self.encodes.load(Ordering::Relaxed),
+            encode_time: Duration::from_nanos(self.encode_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CodecMetrics, HeaderError, HeaderErrors};
+
+    #[test]
+    fn counts_each_header_error_under_its_own_name() {
+        let metrics = CodecMetrics::default();
+        let errors = [
+            HeaderError::Truncated,
+            HeaderError::Misaligned,
+            HeaderError::InvalidMagic(*b"NOPE"),
+            HeaderError::UnsupportedVersion {
+                found: 9,
+                expected: 1,
+            },
+            HeaderError::InvalidKind(7),
+            HeaderError::ReservedFlags(0x80),
+        ];
+        for (times, error) in (1..).zip(&errors) {
+            for _ in 0..times {
+                metrics.header_error(error);
+            }
+        }
+        let expected = HeaderErrors {
+            truncated: 1,
+            misaligned: 2,
+            invalid_magic: 3,
+            unsupported_version: 4,
+            invalid_kind: 5,
+            reserved_flags: 6,
+        };
+        assert_eq!(metrics.snapshot().header_errors, expected);
+        assert_eq!(expected.total(), 21);
+    }
+}
@@ -0,0 +1,108 @@
+use {
+    crate::error::ProtoError,
+    serde::{de::DeserializeOwned, Deserialize, Serialize},
+};
+
+/// Turns typed payloads into frame bytes and back.
+pub trait PayloadCodec {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, ProtoError>;
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, ProtoError>;
+
+    /// Whether the format records field and variant names, which tagged
+    /// enums such as `Message` rely on.
+    fn self_describing(&self) -> bool {
+        true
+    }
+}
+
+/// Human readable, easiest to debug.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json;
+
+/// Compact and self-describing.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePack;
+
+/// Smallest on the wire, but both sides must agree on the exact schema.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Bincode;
+
+impl PayloadCodec for Json {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, ProtoError> {
+        Ok(serde_json::to_vec(value)?)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, ProtoError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+impl PayloadCodec for MessagePack {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, ProtoError> {
+        Ok(rmp_serde::to_vec_named(value)?)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, ProtoError> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+}
+
+impl PayloadCodec for Bincode {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, ProtoError> {
+        Ok(bincode::serde::encode_to_vec(
+            value,
+            bincode::config::standard(),
+        )?)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, ProtoError> {
+        let (value, _) = bincode::serde::decode_from_slice(bytes, bincode::config::standard())?;
+        Ok(value)
+    }
+
+    fn self_describing(&self) -> bool {
+        false
+    }
+}
+
+/// Payload format picked during the handshake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadFormat {
+    #[default]
+    Json,
+    MessagePack,
+    Bincode,
+}
+
+impl PayloadFormat {
+    /// Every format this build speaks, most compact first.
+    pub const ALL: [Self; 3] = [Self::Bincode, Self::MessagePack, Self::Json];
+}
+
+impl PayloadCodec for PayloadFormat {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, ProtoError> {
+        match self {
+            Self::Json => Json.encode(value),
+            Self::MessagePack => MessagePack.encode(value),
+            Self::Bincode => Bincode.encode(value),
+        }
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, ProtoError> {
+        match self {
+            Self::Json => Json.decode(bytes),
+            Self::MessagePack => MessagePack.decode(bytes),
+            Self::Bincode => Bincode.decode(bytes),
+        }
+    }
+
+    fn self_describing(&self) -> bool {
+        match self {
+            Self::Json => Json.self_describing(),
+            Self::MessagePack => MessagePack.self_describing(),
+            Self::Bincode => Bincode.self_describing(),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use {
+    crate::{
+        codec::{CorrelationId, Frame},
+        error::ProtoError,
+    },
+    std::{collections::HashMap, time::Duration},
+    tokio::sync::oneshot,
+};
+
+/// Tags outgoing requests with correlation IDs and routes replies back to
+/// whoever is waiting on them, the way `RpcReplyPort` does for actor calls.
+#[derive(Debug, Default)]
+pub struct RequestTracker {
+    next: u32,
+    pending: HashMap<CorrelationId, oneshot::Sender<Frame>>,
+}
+
+/// Receiving end of a tracked request.
+#[derive(Debug)]
+pub struct ReplyPort {
+    id: CorrelationId,
+    reply: oneshot::Receiver<Frame>,
+}
+
+impl RequestTracker {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tags `frame` with a fresh correlation ID and returns the port its reply arrives on.
+    pub fn track(&mut self, frame: Frame) -> (Frame, ReplyPort) {
+        self.pending.retain(|_, waiter| !waiter.is_closed());
+
+        let mut id = CorrelationId(self.next);
+        while self.pending.contains_key(&id) {
+            id = CorrelationId(id.0.wrapping_add(1));
+        }
+        self.next = id.0.wrapping_add(1);
+
+        let (waiter, reply) = oneshot::channel();
+        self.pending.insert(id, waiter);
+        (frame.with_correlation(id), ReplyPort { id, reply })
+    }
+
+    /// Hands a reply to its waiter; frames nobody is waiting for are returned.
+    pub fn resolve(&mut self, frame: Frame) -> Option<Frame> {
+        let Some(waiter) = frame.correlation().and_then(|id| self.pending.remove(&id)) else {
+            return Some(frame);
+        };
+        waiter.send(frame).err()
+    }
+
+    /// Number of requests still waiting for a reply.
+    #[inline]
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl ReplyPort {
+    #[inline]
+    pub fn id(&self) -> CorrelationId {
+        self.id
+    }
+
+    /// Waits up to `timeout` for the reply.
+    pub async fn recv(self, timeout: Duration) -> Result<Frame, ProtoError> {
+        match tokio::time::timeout(timeout, self.reply).await {
+            Ok(Ok(frame)) => Ok(frame),
+            Ok(Err(_)) => Err(ProtoError::ReplyDropped(self.id)),
+            Err(_) => Err(ProtoError::ReplyTimeout(self.id)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::RequestTracker,
+        crate::{
+            codec::Frame,
+            error::ProtoError,
+            header::{Flags, Kind},
+        },
+        bytes::Bytes,
+        std::time::Duration,
+    };
+
+    fn frame(payload: &'static [u8]) -> Frame {
+        Frame::new(Kind::Game, Flags::empty(), Bytes::from_static(payload))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn routes_replies_and_times_out() {
+        let mut tracker = RequestTracker::new();
+        let (first, answered) = tracker.track(frame(b"first"));
+        let (_, unanswered) = tracker.track(frame(b"second"));
+        assert_ne!(answered.id(), unanswered.id());
+        assert_eq!(first.correlation(), Some(answered.id()));
+
+        let reply = frame(b"reply").with_correlation(answered.id());
+        assert!(tracker.resolve(reply).is_none());
+        assert!(tracker.resolve(frame(b"unsolicited")).is_some());
+
+        let reply = answered.recv(Duration::from_secs(1)).await.expect("reply");
+        assert_eq!(reply.payload(), &b"reply"[..]);
+
+        let id = unanswered.id();
+        assert!(matches!(
+            unanswered.recv(Duration::from_secs(1)).await,
+            Err(ProtoError::ReplyTimeout(timed_out)) if timed_out == id
+        ));
+        let late = frame(b"late").with_correlation(id);
+        assert!(tracker.resolve(late).is_some());
+        assert_eq!(tracker.pending(), 0);
+    }
+}
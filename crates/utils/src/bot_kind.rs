@@ -0,0 +1,10 @@
+use serde::Deserialize;
+/// The strategies a bot player can use
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotKind {
+    #[default]
+    Random,
+    Keyword,
+    JudgeModel,
+}
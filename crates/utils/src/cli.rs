@@ -1,4 +1,5 @@
-use clap::{ArgGroup, Parser};
+use clap::{ArgGroup, Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -10,7 +11,11 @@ use clap::{ArgGroup, Parser};
         .required(true)
         .args(["ip", "players"])
 ))]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Number of players (host only)
     #[arg(short, long)]
     pub players: Option<usize>,
@@ -19,24 +24,76 @@ pub struct Args {
     #[arg(short, long)]
     pub bots: Option<usize>,
 
-    /// IP address to connect to (client only)
+    /// Address to connect to, `host:port`, `ws://host:port` or `unix:<path>` (client only)
     #[arg(short, long)]
     pub ip: Option<String>,
+
+    /// Display name shown to other players (client only)
+    #[arg(short, long)]
+    pub name: Option<String>,
+
+    /// Record every frame sent or received to this file
+    #[arg(short, long)]
+    pub capture: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Inspect a frame capture recorded with `--capture`
+    #[command(subcommand)]
+    Capture(CaptureCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CaptureCommand {
+    /// Print every captured frame, decoding known payloads
+    Print { path: PathBuf },
+    /// Replay a capture through the frame codec at the pace it was recorded
+    Replay {
+        path: PathBuf,
+        /// Only show this connection
+        #[arg(short, long)]
+        stream: Option<u32>,
+        /// Playback speed, 2.0 plays twice as fast
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+    },
 }
 
 pub enum Mode {
-    Host { players: usize, bots: usize },
-    Client { ip: String },
+    Host {
+        players: usize,
+        bots: usize,
+        capture: Option<PathBuf>,
+    },
+    Client {
+        ip: String,
+        name: String,
+        capture: Option<PathBuf>,
+    },
+    Capture(CaptureCommand),
 }
 
 pub fn parse_args() -> Mode {
     let args = Args::parse();
 
+    if let Some(Command::Capture(command)) = args.command {
+        return Mode::Capture(command);
+    }
     if let Some(ip) = args.ip {
-        Mode::Client { ip }
+        let name = args.name.unwrap_or_else(|| String::from("player"));
+        Mode::Client {
+            ip,
+            name,
+            capture: args.capture,
+        }
     } else {
         let players = args.players.unwrap_or(0);
         let bots = args.bots.unwrap_or(0);
-        Mode::Host { players, bots }
+        Mode::Host {
+            players,
+            bots,
+            capture: args.capture,
+        }
     }
 }
@@ -1,10 +1,116 @@
 use {
-    crate::game_mode::GameMode,
+    crate::{bot_kind::BotKind, game_mode::GameMode},
+    apples_protocol::PayloadFormat,
     serde::Deserialize,
     std::collections::HashMap,
-    std::{net::SocketAddrV4, path::PathBuf},
+    std::{
+        net::SocketAddrV4,
+        path::{Path, PathBuf},
+        time::Duration,
+    },
 };
 
+/// Liveness checks on connections between host and clients.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct Heartbeat {
+    /// Seconds between pings.
+    interval_secs: u64,
+    /// Seconds without any traffic after which the peer counts as gone.
+    idle_timeout_secs: u64,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self {
+            interval_secs: 5,
+            idle_timeout_secs: 20,
+        }
+    }
+}
+
+impl Heartbeat {
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_secs)
+    }
+
+    pub fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_timeout_secs)
+    }
+
+    /// Rejects intervals the ticker cannot run and timeouts that would drop
+    /// every peer between two pings.
+    fn validate(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.interval_secs > 0,
+            "heartbeat.interval_secs must be at least 1"
+        );
+        anyhow::ensure!(
+            self.idle_timeout_secs > self.interval_secs,
+            "heartbeat.idle_timeout_secs ({}) must be longer than heartbeat.interval_secs ({})",
+            self.idle_timeout_secs,
+            self.interval_secs
+        );
+        Ok(())
+    }
+}
+
+/// Optional TLS on top of the TCP connection between host and clients.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Tls {
+    enabled: bool,
+    /// PEM certificate the host presents; clients trust it when no pin is set.
+    cert_path: PathBuf,
+    /// PEM private key of the host certificate.
+    key_path: PathBuf,
+    /// Whether the host generates a self-signed certificate when none exists.
+    self_signed: bool,
+    /// Name the host certificate is issued for and clients check it against.
+    server_name: String,
+    /// Hex SHA-256 of the host certificate; clients accept only that certificate.
+    pinned_sha256: Option<String>,
+}
+
+impl Default for Tls {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cert_path: PathBuf::from("./data/tls/cert.pem"),
+            key_path: PathBuf::from("./data/tls/key.pem"),
+            self_signed: true,
+            server_name: String::from("localhost"),
+            pinned_sha256: None,
+        }
+    }
+}
+
+impl Tls {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn cert_path(&self) -> &Path {
+        &self.cert_path
+    }
+
+    pub fn key_path(&self) -> &Path {
+        &self.key_path
+    }
+
+    pub fn self_signed(&self) -> bool {
+        self.self_signed
+    }
+
+    pub fn server_name(&self) -> &str {
+        &self.server_name
+    }
+
+    pub fn pinned_sha256(&self) -> Option<&str> {
+        self.pinned_sha256.as_deref()
+    }
+}
+
 #[derive(Deserialize)]
 pub struct Config {
     red_deck_fp: String,
@@ -12,12 +118,25 @@ pub struct Config {
     win_condition: HashMap<String, usize>,
     game_mode: GameMode,
     socket_addr: SocketAddrV4,
+    /// Unix domain socket the host listens on instead of `socket_addr`.
+    unix_socket_path: Option<PathBuf>,
+    /// Address the host additionally accepts WebSocket clients on.
+    websocket_addr: Option<SocketAddrV4>,
+    #[serde(default)]
+    bot_strategy: BotKind,
+    #[serde(default)]
+    heartbeat: Heartbeat,
+    /// Payload format the client asks for first; the host speaks all of them.
+    payload_format: Option<PayloadFormat>,
+    #[serde(default)]
+    tls: Tls,
 }
 
 impl Config {
     pub fn parse_config(path: PathBuf) -> anyhow::Result<Config> {
         let str = std::fs::read_to_string(path)?;
-        let config = toml::from_str(&str)?;
+        let config: Config = toml::from_str(&str)?;
+        config.heartbeat.validate()?;
         Ok(config)
     }
 
@@ -52,4 +171,44 @@ impl Config {
     pub fn socket(&self) -> SocketAddrV4 {
         self.socket_addr
     }
+
+    pub fn unix_socket_path(&self) -> Option<&Path> {
+        self.unix_socket_path.as_deref()
+    }
+
+    pub fn websocket_addr(&self) -> Option<SocketAddrV4> {
+        self.websocket_addr
+    }
+
+    pub fn bot_strategy(&self) -> BotKind {
+        self.bot_strategy
+    }
+
+    pub fn heartbeat(&self) -> Heartbeat {
+        self.heartbeat
+    }
+
+    pub fn payload_format(&self) -> Option<PayloadFormat> {
+        self.payload_format
+    }
+
+    pub fn tls(&self) -> &Tls {
+        &self.tls
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Heartbeat;
+
+    #[test]
+    fn rejects_unusable_heartbeats() {
+        let parse = |toml: &str| toml::from_str::<Heartbeat>(toml).expect("parse");
+        assert!(parse("").validate().is_ok());
+        assert!(parse("interval_secs = 0").validate().is_err());
+        assert!(parse("idle_timeout_secs = 0").validate().is_err());
+        assert!(parse("interval_secs = 5\nidle_timeout_secs = 5")
+            .validate()
+            .is_err());
+    }
 }
@@ -7,4 +7,10 @@ pub enum GameMode {
     ApplesEyeView,
     BadHarvest,
     TwoForOne,
+    /// The judge picks the worst match instead of the best
+    CrabApples,
+    /// The winner of a round judges the next one
+    AppleTurnovers,
+    /// Red cards are played before the green card is drawn
+    ApplePotpourri,
 }
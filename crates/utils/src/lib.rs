@@ -1,3 +1,4 @@
+pub mod bot_kind;
 pub mod cli;
 pub mod config;
 pub mod consts;
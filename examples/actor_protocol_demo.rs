@@ -1,10 +1,10 @@
 use {
     anyhow::Result,
     apples_core::{cards::card::Card, RedCard},
-    apples_protocol::{Frame, FrameCodec, Kind},
+    apples_protocol::{Flags, Frame, FrameCodec, Kind},
     bytes::Bytes,
     futures_util::{stream::SplitSink, stream::SplitStream, SinkExt, StreamExt},
-    ractor::{async_trait, Actor, ActorProcessingErr, ActorRef, MessagingErr},
+    ractor::{Actor, ActorProcessingErr, ActorRef, MessagingErr},
     serde_json::{from_slice, to_vec},
     std::time::Duration,
     tokio::{net::{TcpListener, TcpStream}, task::JoinHandle, time::sleep},
@@ -23,7 +23,7 @@ struct WriterState {
 
 struct WriterActor;
 
-#[async_trait]
+#[cfg_attr(feature = "async-trait", ractor::async_trait)]
 impl Actor for WriterActor {
     type Msg = WriterMsg;
     type State = WriterState;
@@ -80,7 +80,7 @@ struct ReaderState {
 
 struct ReaderActor;
 
-#[async_trait]
+#[cfg_attr(feature = "async-trait", ractor::async_trait)]
 impl Actor for ReaderActor {
     type Msg = ();
     type State = ReaderState;
@@ -143,7 +143,7 @@ struct ClientState {
 
 struct ClientCoordinator;
 
-#[async_trait]
+#[cfg_attr(feature = "async-trait", ractor::async_trait)]
 impl Actor for ClientCoordinator {
     type Msg = ClientEvent;
     type State = ClientState;
@@ -171,7 +171,7 @@ impl Actor for ClientCoordinator {
                     .map_err(|err| ActorProcessingErr::from(err.to_string()))?;
                 state
                     .writer
-                    .cast(WriterMsg::Frame(Frame::new(Kind::Game, 0, Bytes::from(payload))))
+                    .cast(WriterMsg::Frame(Frame::new(Kind::Game, Flags::empty(), Bytes::from(payload))))
                     .map_err(into_actor_err)?;
             }
             ClientEvent::ConnectionClosed => {
@@ -199,7 +199,7 @@ async fn main() -> Result<()> {
 
         let prompt = Frame::new(
             Kind::Game,
-            0,
+            Flags::empty(),
             Bytes::from_static(b"Judge: play your best red card"),
         );
         framed.send(prompt).await?;
@@ -1,10 +1,8 @@
 use {
     anyhow::Result,
-    apples_core::{cards::card::Card, RedCard},
-    apples_protocol::{Frame, FrameCodec, Kind},
-    bytes::Bytes,
+    apples_core::{PlayerId, RedCard},
+    apples_protocol::{FrameCodec, Message},
     futures_util::{SinkExt, StreamExt},
-    serde_json::{from_slice, to_vec},
     tokio::net::{TcpListener, TcpStream},
     tokio_util::codec::Framed,
 };
@@ -19,19 +17,26 @@ async fn main() -> Result<()> {
         let mut framed = Framed::new(socket, FrameCodec::with_default_limit());
 
         // Ask the connected player to submit their best red card.
-        let prompt = Frame::new(
-            Kind::Game,
-            0,
-            Bytes::from_static(b"Judge: play a red card!"),
-        );
-        framed.send(prompt).await?;
-
-        // Await the player's response and decode the red card without copying the payload.
-        if let Some(frame) = framed.next().await.transpose()? {
-            let card: RedCard = from_slice(frame.payload())?;
-            println!("judge received red card: {}", card);
+        let prompt = Message::JudgeRequest {
+            submissions: Vec::new(),
+            worst: false,
+        };
+        framed.send(prompt.encode()?).await?;
+
+        // Await the player's response and decode the typed message.
+        if let Some(frame) = framed.next().await.transpose()?
+            && let Message::SubmitRed { cards } = Message::decode(&frame)?
+        {
+            cards
+                .iter()
+                .for_each(|card| println!("judge received red card: {}", card));
         }
 
+        let over = Message::GameOver {
+            winner: PlayerId(0),
+        };
+        framed.send(over.encode()?).await?;
+
         Ok::<_, anyhow::Error>(())
     });
 
@@ -39,20 +44,25 @@ async fn main() -> Result<()> {
     let mut framed = Framed::new(stream, FrameCodec::with_default_limit());
 
     // Wait for the judge's prompt before responding.
-    if let Some(frame) = framed.next().await.transpose()? {
-        println!(
-            "player received prompt: {}",
-            String::from_utf8_lossy(frame.payload())
-        );
-
-        let red_card = RedCard::new(
-            42usize,
-            "Fresh Socks",
-            "The warm comfort of dryer-fresh socks",
-        );
-        let payload = to_vec(&red_card)?;
-        let reply = Frame::new(Kind::Game, 0, Bytes::from(payload));
-        framed.send(reply).await?;
+    while let Some(frame) = framed.next().await.transpose()? {
+        match Message::decode(&frame)? {
+            Message::JudgeRequest { .. } => {
+                println!("player received prompt: play a red card!");
+
+                let card = RedCard::new(
+                    42usize,
+                    "Fresh Socks",
+                    "The warm comfort of dryer-fresh socks",
+                );
+                let cards = vec![card];
+                framed.send(Message::SubmitRed { cards }.encode()?).await?;
+            }
+            Message::GameOver { winner } => {
+                println!("game over, winner: {winner}");
+                break;
+            }
+            other => println!("player: unexpected message {other:?}"),
+        }
     }
 
     server.await??;
@@ -0,0 +1,130 @@
+use crate::{actors::game_coordinator::CoordinatorMsg, bots::BotStrategy};
+use ::ractor::{ActorProcessingErr, ActorRef};
+use apples_core::{cards::card::Card, GreenCard, PlayerId, RedCard};
+use apples_protocol::{ErrorCode, ErrorFrame, Message};
+use dsl_ractor::{actor, actor_handle, actor_pre_start};
+
+pub struct BotArgs {
+    pub coordinator: ActorRef<CoordinatorMsg>,
+    pub strategy: Box<dyn BotStrategy>,
+}
+
+/// A seat played by a `BotStrategy`; it receives the same messages as a human client.
+#[actor(msg = Message, state = BotState, args = BotArgs)]
+pub(crate) struct BotPlayer;
+
+pub(crate) struct BotState {
+    coordinator: ActorRef<CoordinatorMsg>,
+    strategy: Box<dyn BotStrategy>,
+    id: Option<PlayerId>,
+    hand: Vec<RedCard>,
+    round: Option<(PlayerId, Vec<GreenCard>)>,
+}
+
+impl BotPlayer {
+    actor_pre_start!(Ok(BotState {
+        coordinator: args.coordinator,
+        strategy: args.strategy,
+        id: None,
+        hand: Vec::new(),
+        round: None,
+    }));
+
+    actor_handle!({
+        match msg {
+            Message::Welcome { player_id } => state.id = Some(player_id),
+            Message::DealHand { cards } => state.hand.extend(cards),
+            Message::ReplaceHand { cards } => state.hand = cards,
+            Message::RevealGreen { judge, card } => Self::reveal(state, judge, vec![card]),
+            Message::RevealGreenPair {
+                judge,
+                first,
+                second,
+            } => Self::reveal(state, judge, vec![first, second]),
+            Message::PlayRequest { count } => {
+                let Some(id) = state.id else {
+                    return Ok(());
+                };
+                // Blind rounds ask for red cards before any green card is revealed.
+                let greens = state.round.as_ref().map_or(&[][..], |(_, greens)| greens);
+                if count == 0 || state.hand.len() < count {
+                    let reason = format!("cannot play {count} of {} cards", state.hand.len());
+                    return Self::pass(state, id, ErrorCode::OUT_OF_CARDS, reason);
+                }
+                // The cards leave the hand once the host confirms them.
+                let mut options = state.hand.clone();
+                let mut cards = Vec::with_capacity(count);
+                for _ in 0..count {
+                    let index = state.strategy.choose_red(greens, &options);
+                    let red = options.remove(index);
+                    tracing::debug!("Bot {id} plays {}", red.name());
+                    cards.push(red);
+                }
+                ractor::cast!(
+                    state.coordinator,
+                    CoordinatorMsg::Inbound(id, Message::SubmitRed { cards })
+                )?;
+            }
+            Message::Submitted { cards } => state
+                .hand
+                .retain(|held| !cards.iter().any(|card| card.id() == held.id())),
+            Message::JudgeRequest { submissions, worst } => {
+                let Some(id) = state.id else {
+                    return Ok(());
+                };
+                let leads: Vec<RedCard> = submissions
+                    .iter()
+                    .filter_map(|cards| cards.first().cloned())
+                    .collect();
+                let Some((_, greens)) = &state.round else {
+                    let reason = String::from("no green card was revealed");
+                    return Self::pass(state, id, ErrorCode::ILLEGAL_MOVE, reason);
+                };
+                if leads.is_empty() || leads.len() != submissions.len() {
+                    let reason = String::from("nothing to pick from");
+                    return Self::pass(state, id, ErrorCode::ILLEGAL_MOVE, reason);
+                }
+                let index = if worst {
+                    state.strategy.judge_worst(greens, &leads)
+                } else {
+                    state.strategy.judge(greens, &leads)
+                };
+                let choice = Message::JudgeChoice {
+                    card: leads[index].id(),
+                };
+                ractor::cast!(state.coordinator, CoordinatorMsg::Inbound(id, choice))?;
+            }
+            Message::RoundResult { greens, reds, .. } => {
+                if let Some((judge, _)) = state.round.take()
+                    && let Some(red) = reds.first()
+                {
+                    state.strategy.observe(judge, &greens, red);
+                }
+            }
+            Message::GameOver { .. } => myself.stop(None),
+            Message::Error(error) => tracing::warn!("Host refused a bot move: {error}"),
+            _ => {}
+        }
+        Ok(())
+    });
+}
+
+impl BotPlayer {
+    /// Tells the coordinator the bot sits this request out.
+    fn pass(
+        state: &BotState,
+        id: PlayerId,
+        code: ErrorCode,
+        reason: String,
+    ) -> Result<(), ActorProcessingErr> {
+        tracing::warn!("Bot {id} passes: {reason}");
+        let error = Message::Error(ErrorFrame::new(code, reason));
+        ractor::cast!(state.coordinator, CoordinatorMsg::Inbound(id, error))?;
+        Ok(())
+    }
+
+    fn reveal(state: &mut BotState, judge: PlayerId, greens: Vec<GreenCard>) {
+        state.strategy.reveal(judge, &greens);
+        state.round = Some((judge, greens));
+    }
+}
@@ -1,29 +1,227 @@
-use apples_core::GreenCard;
+use ::ractor::ActorProcessingErr;
+use apples_core::{cards::card::Card, GreenCard, PlayerId, RedCard};
+use apples_protocol::{ErrorCode, ErrorFrame, Frame, Message, PayloadFormat};
 use dsl_ractor::{actor, actor_handle, actor_pre_start};
-use ractor::RpcReplyPort;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader, Lines, Stdin},
+    sync::mpsc::UnboundedSender,
+};
 
+/// Frames queued for the task writing to the host.
+pub(crate) type FrameSink = UnboundedSender<Frame>;
+
+#[derive(Debug)]
 pub(crate) enum PlayerMsg {
-    ChooseCard,
-    SendCard(GreenCard),
+    /// Message received from the host.
+    Server(Message),
+    /// Prompt for the given amount of red cards; they stay in the hand until
+    /// the host confirms them with `Submitted`.
+    ChooseCards(usize),
+    SendCards(Vec<RedCard>),
 }
 
-#[actor(msg=PlayerMsg,state=(),args=usize)]
+#[actor(msg = PlayerMsg, state = PlayerState, args = (PlayerId, FrameSink, PayloadFormat))]
 pub(crate) struct ClientPlayer;
 
+pub(crate) struct PlayerState {
+    id: PlayerId,
+    sink: FrameSink,
+    format: PayloadFormat,
+    input: Lines<BufReader<Stdin>>,
+    hand: Vec<RedCard>,
+    greens: Vec<GreenCard>,
+    /// Red cards the host asked for and has not accepted yet.
+    pending: Option<usize>,
+}
+
+impl PlayerState {
+    fn send(&self, msg: Message) -> Result<(), ActorProcessingErr> {
+        self.sink
+            .send(msg.encode_with(&self.format)?)
+            .map_err(|_| ActorProcessingErr::from("connection to the host is closed"))?;
+        Ok(())
+    }
+
+    fn reveal(&mut self, judge: PlayerId, greens: Vec<GreenCard>) {
+        greens.iter().for_each(|green| println!("\n{green}"));
+        self.greens = greens;
+        if judge == self.id {
+            println!("You are the judge this round");
+        } else {
+            println!("{judge} is judging this round");
+        }
+    }
+
+    /// Tells the host this player cannot answer its request this round.
+    fn pass(&self, code: ErrorCode, reason: &str) -> Result<(), ActorProcessingErr> {
+        println!("{reason}, passing this round");
+        self.send(Message::Error(ErrorFrame::new(code, reason)))
+    }
+
+    /// Reads an index in `0..len` from stdin, asking again on invalid input;
+    /// `None` when there is nothing to pick from.
+    async fn prompt_index(&mut self, len: usize) -> Result<Option<usize>, ActorProcessingErr> {
+        if len == 0 {
+            return Ok(None);
+        }
+        loop {
+            println!("Pick a card [0-{}]:", len - 1);
+            let Some(line) = self.input.next_line().await? else {
+                return Err(ActorProcessingErr::from("stdin closed"));
+            };
+            match line.trim().parse::<usize>() {
+                Ok(index) if index < len => return Ok(Some(index)),
+                _ => println!("'{}' is not a valid choice", line.trim()),
+            }
+        }
+    }
+}
+
 impl ClientPlayer {
-    actor_pre_start!(Ok(()));
+    actor_pre_start!({
+        let (id, sink, format) = args;
+        Ok(PlayerState {
+            id,
+            sink,
+            format,
+            input: BufReader::new(tokio::io::stdin()).lines(),
+            hand: Vec::new(),
+            greens: Vec::new(),
+            pending: None,
+        })
+    });
 
     actor_handle!({
         match msg {
-            PlayerMsg::ChooseCard => {
-                let card = todo!();
-                ractor::cast!(myself, PlayerMsg::SendCard(card))?;
+            PlayerMsg::Server(Message::DealHand { cards }) => {
+                state.hand.extend(cards);
+            }
+            PlayerMsg::Server(Message::ReplaceHand { cards }) => {
+                println!("Bad harvest! Your hand was redrawn");
+                state.hand = cards;
+            }
+            PlayerMsg::Server(Message::RevealGreen { judge, card }) => {
+                state.reveal(judge, vec![card]);
             }
-            PlayerMsg::SendCard(card) => {
-                todo!("add connection here ");
+            PlayerMsg::Server(Message::RevealGreenPair {
+                judge,
+                first,
+                second,
+            }) => {
+                println!("\nTwo for one! One red card is judged against both");
+                state.reveal(judge, vec![first, second]);
+            }
+            PlayerMsg::Server(Message::PlayRequest { count }) => {
+                state.pending = Some(count);
+                ractor::cast!(myself, PlayerMsg::ChooseCards(count))?;
+            }
+            PlayerMsg::Server(Message::Submitted { cards }) => {
+                state.pending = None;
+                state
+                    .hand
+                    .retain(|held| !cards.iter().any(|card| card.id() == held.id()));
+            }
+            PlayerMsg::Server(Message::JudgeRequest { submissions, worst }) => {
+                if !state.greens.is_empty() {
+                    let verdict = if worst { "worst" } else { "best" };
+                    let greens = Self::names(&state.greens);
+                    println!("Pick the {verdict} match for {greens}");
+                }
+                for (index, cards) in submissions.iter().enumerate() {
+                    println!("  [{index}] {}", Self::names(cards));
+                }
+                let index = state.prompt_index(submissions.len()).await?;
+                let Some(card) = index
+                    .and_then(|index| submissions[index].first())
+                    .map(|card| card.id())
+                else {
+                    return state.pass(ErrorCode::ILLEGAL_MOVE, "Nothing to pick from");
+                };
+                state.send(Message::JudgeChoice { card })?;
+            }
+            PlayerMsg::Server(Message::RoundResult {
+                winners,
+                greens,
+                reds,
+            }) => {
+                let greens = Self::names(&greens);
+                if reds.is_empty() {
+                    println!("The round was called off, nobody scores");
+                    return Ok(());
+                }
+                println!("{} decided {greens}", Self::names(&reds));
+                match winners.as_slice() {
+                    [] => println!("Nobody scores this round"),
+                    winners => winners
+                        .iter()
+                        .for_each(|winner| println!("{winner} wins {greens}")),
+                }
+            }
+            PlayerMsg::Server(Message::ScoreUpdate { scores }) => {
+                println!("Scores:");
+                for (id, score) in scores {
+                    let you = if id == state.id { " (you)" } else { "" };
+                    println!("  {id}{you}: {score}");
+                }
+            }
+            PlayerMsg::Server(Message::GameOver { winner }) => {
+                if winner == state.id {
+                    println!("Game over, you win!");
+                } else {
+                    println!("Game over, {winner} wins");
+                }
+                myself.stop(Some("game over".into()));
+            }
+            PlayerMsg::Server(Message::Error(error)) => {
+                println!("The host refused that: {error}");
+                if let Some(count) = state.pending {
+                    ractor::cast!(myself, PlayerMsg::ChooseCards(count))?;
+                }
+            }
+            PlayerMsg::Server(other) => {
+                tracing::warn!("Unexpected message from host: {other:?}");
+            }
+            PlayerMsg::ChooseCards(count) => {
+                if count == 0 || state.hand.len() < count {
+                    state.pending = None;
+                    return state.pass(ErrorCode::OUT_OF_CARDS, "Not enough cards left to play");
+                }
+                if count > 1 {
+                    println!("Play {count} cards");
+                }
+                let mut options = state.hand.clone();
+                let mut cards = Vec::with_capacity(count);
+                for _ in 0..count {
+                    println!("Your hand:");
+                    Self::render(&options);
+                    let Some(index) = state.prompt_index(options.len()).await? else {
+                        break;
+                    };
+                    cards.push(options.remove(index));
+                }
+                ractor::cast!(myself, PlayerMsg::SendCards(cards))?;
+            }
+            PlayerMsg::SendCards(cards) => {
+                state.send(Message::SubmitRed { cards })?;
             }
         }
 
         Ok(())
     });
 }
+
+impl ClientPlayer {
+    fn render(cards: &[RedCard]) {
+        for (index, card) in cards.iter().enumerate() {
+            println!("  [{index}] {} - {}", card.name(), card.description());
+        }
+    }
+
+    fn names(cards: &[impl Card]) -> String {
+        cards
+            .iter()
+            .map(|card| card.name())
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+}
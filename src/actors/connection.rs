@@ -0,0 +1,199 @@
+use crate::transport::Transport;
+use ::ractor::{Actor, ActorId, ActorProcessingErr, ActorRef, DerivedActorRef};
+use apples_protocol::{Frame, FrameCodec, Kind, Message, PayloadFormat};
+use apples_utils::config::Heartbeat;
+use futures_util::{stream::SplitSink, SinkExt, StreamExt};
+use tokio::{sync::oneshot, task::JoinHandle};
+use tokio_util::codec::Framed;
+
+/// A transport whose protocol version was already negotiated.
+pub type FramedTransport = Framed<Box<dyn Transport>, FrameCodec>;
+
+#[derive(Debug)]
+pub enum ConnectionMsg {
+    /// Write a raw frame to the socket.
+    Send(Frame),
+    /// Encode a typed message and write it to the socket.
+    Deliver(Message),
+    /// Ping the peer so its idle timer does not fire.
+    Heartbeat,
+    /// Flush and close the socket, then stop the actor.
+    Close,
+}
+
+impl From<Message> for ConnectionMsg {
+    #[inline]
+    fn from(msg: Message) -> Self {
+        Self::Deliver(msg)
+    }
+}
+
+impl TryFrom<ConnectionMsg> for Message {
+    type Error = ConnectionMsg;
+
+    #[inline]
+    fn try_from(msg: ConnectionMsg) -> Result<Self, ConnectionMsg> {
+        match msg {
+            ConnectionMsg::Deliver(msg) => Ok(msg),
+            other => Err(other),
+        }
+    }
+}
+
+/// Events reported by a connection to its parent actor.
+#[derive(Debug)]
+pub enum ConnectionEvent {
+    /// A frame was decoded from the socket.
+    Frame(ActorId, Frame),
+    /// The socket was closed by either side, failed or went quiet for longer
+    /// than the idle timeout; carries the reason, if any. Sent exactly once,
+    /// when the connection stops.
+    Disconnected(ActorId, Option<String>),
+}
+
+pub struct ConnectionArgs {
+    pub stream: FramedTransport,
+    pub parent: DerivedActorRef<ConnectionEvent>,
+    pub heartbeat: Heartbeat,
+    /// Payload format negotiated in the handshake.
+    pub format: PayloadFormat,
+}
+
+/// Owns a single socket: writes frames on request and forwards decoded frames to the parent.
+///
+/// Pings the peer every heartbeat interval, answers its pings and drops the
+/// connection once nothing arrived for the idle timeout.
+pub(crate) struct Connection;
+
+pub(crate) struct ConnectionState {
+    peer: String,
+    format: PayloadFormat,
+    parent: DerivedActorRef<ConnectionEvent>,
+    sink: SplitSink<FramedTransport, Frame>,
+    pump: JoinHandle<()>,
+    /// Why the read side ended, once it has.
+    ended: oneshot::Receiver<Option<String>>,
+    /// Why the write side failed, if it has.
+    failure: Option<String>,
+    ticker: JoinHandle<()>,
+}
+
+impl Connection {
+    /// The ping or pong carried by a keep-alive frame.
+    fn keepalive(frame: &Frame, format: PayloadFormat) -> Option<Message> {
+        if frame.header().kind() != Kind::Control {
+            return None;
+        }
+        match Message::decode_with(frame, &format) {
+            Ok(msg @ (Message::Ping | Message::Pong)) => Some(msg),
+            _ => None,
+        }
+    }
+}
+
+#[cfg_attr(feature = "async-trait", ractor::async_trait)]
+impl Actor for Connection {
+    type Msg = ConnectionMsg;
+    type State = ConnectionState;
+    type Arguments = ConnectionArgs;
+
+    async fn pre_start(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        let ConnectionArgs {
+            stream,
+            parent,
+            heartbeat,
+            format,
+        } = args;
+        let peer = stream.get_ref().peer();
+        let (sink, mut stream) = stream.split();
+
+        let id = myself.get_id();
+        let ticker = myself.send_interval(heartbeat.interval(), || ConnectionMsg::Heartbeat);
+        let idle_timeout = heartbeat.idle_timeout();
+        let (end, ended) = oneshot::channel();
+        let events = parent.clone();
+        let pump = tokio::spawn(async move {
+            let reason = loop {
+                let Ok(next) = tokio::time::timeout(idle_timeout, stream.next()).await else {
+                    break Some(format!("silent for {idle_timeout:?}"));
+                };
+                match next {
+                    Some(Ok(frame)) => match Self::keepalive(&frame, format) {
+                        Some(Message::Ping) => {
+                            let _ = myself.cast(ConnectionMsg::Deliver(Message::Pong));
+                        }
+                        Some(_) => {}
+                        None => {
+                            if events
+                                .send_message(ConnectionEvent::Frame(id, frame))
+                                .is_err()
+                            {
+                                break Some(String::from("parent is gone"));
+                            }
+                        }
+                    },
+                    Some(Err(e)) => break Some(e.to_string()),
+                    None => break None,
+                }
+            };
+            let _ = end.send(reason);
+            myself.stop(None);
+        });
+
+        Ok(ConnectionState {
+            peer,
+            format,
+            parent,
+            sink,
+            pump,
+            ended,
+            failure: None,
+            ticker,
+        })
+    }
+
+    async fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        msg: Self::Msg,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        let frame = match msg {
+            ConnectionMsg::Send(frame) => frame,
+            ConnectionMsg::Deliver(msg) => msg.encode_with(&state.format)?,
+            ConnectionMsg::Heartbeat => Message::Ping.encode_with(&state.format)?,
+            ConnectionMsg::Close => {
+                myself.stop(None);
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = state.sink.send(frame).await {
+            tracing::warn!("Failed to write to {}: {e}", state.peer);
+            state.failure = Some(e.to_string());
+            myself.stop(None);
+        }
+        Ok(())
+    }
+
+    async fn post_stop(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        state.pump.abort();
+        state.ticker.abort();
+        if let Err(e) = state.sink.close().await {
+            tracing::debug!("Failed to close {}: {e}", state.peer);
+        }
+        let reason = state.ended.try_recv().unwrap_or(state.failure.take());
+        let _ = state
+            .parent
+            .send_message(ConnectionEvent::Disconnected(myself.get_id(), reason));
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use crate::deck_handler::DeckHandler;
 use ::ractor::{ActorProcessingErr, RpcReplyPort};
-use apples_core::{cards::card::Card, GreenCard, GreenDeck, RedCard, RedDeck};
+use apples_core::{cards::card::Card, GreenCard, RedCard};
+use apples_protocol::{ErrorCode, ErrorFrame};
 use core::num::NonZeroUsize;
 use dsl_ractor::{actor, actor_handle, actor_pre_start};
 use thiserror::Error;
@@ -18,10 +19,22 @@ pub enum DealError {
 
 pub type DealResult<T> = std::result::Result<T, DealError>;
 
+impl From<&DealError> for ErrorFrame {
+    fn from(error: &DealError) -> Self {
+        let code = match error {
+            DealError::OutOfCards { .. } | DealError::EmptyDeck => ErrorCode::OUT_OF_CARDS,
+            DealError::InvalidAmount { .. } => ErrorCode::INTERNAL,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
 #[derive(Debug)]
 pub enum DealerMsg {
     DealGreenCards(NonZeroUsize, RpcReplyPort<DealResult<Vec<GreenCard>>>),
     DealRedCards(NonZeroUsize, RpcReplyPort<DealResult<Vec<RedCard>>>),
+    /// Put the discarded red cards under the deck and draw as many fresh ones.
+    Redraw(Vec<RedCard>, RpcReplyPort<DealResult<Vec<RedCard>>>),
     Shuffle,
     GetDeckSizes(RpcReplyPort<(usize, usize)>), // (red_size, green_size)
 }
@@ -60,6 +73,20 @@ impl Dealer {
                     )));
                 }
             }
+            DealerMsg::Redraw(discarded, reply) => {
+                let result = match NonZeroUsize::new(discarded.len()) {
+                    Some(amount) => {
+                        state.deck_handler.return_red_cards(discarded);
+                        Self::deal_cards(amount, &mut state.deck_handler, |h| h.get_red_card())
+                    }
+                    None => Err(DealError::InvalidAmount { amount: 0 }),
+                };
+                if let Err(e) = reply.send(result) {
+                    return Err(ActorProcessingErr::from(format!(
+                        "Failed to send reply: {e}"
+                    )));
+                }
+            }
             DealerMsg::Shuffle => {
                 state.deck_handler.shuffle();
                 tracing::info!("Shuffled decks");
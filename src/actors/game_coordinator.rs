@@ -0,0 +1,732 @@
+use crate::{
+    actors::{
+        dealer::{DealError, DealerMsg},
+        score_handler::{Score, ScoreManagerMsg},
+    },
+    rules::{self, GameRules, Submission},
+};
+use ::ractor::{ActorProcessingErr, ActorRef, DerivedActorRef, RpcReplyPort};
+use apples_core::{
+    cards::card::{Card, CardId},
+    player::player::PlayerHand,
+    GreenCard, PlayerId, RedCard,
+};
+use apples_protocol::{ErrorCode, ErrorFrame, Message};
+use core::num::NonZeroUsize;
+use dsl_ractor::{actor, actor_handle, actor_pre_start};
+use rand::seq::SliceRandom;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum CoordinatorError {
+    #[error("lobby is full: all {seats} seats are taken")]
+    LobbyFull { seats: usize },
+}
+
+pub type CoordinatorResult<T> = std::result::Result<T, CoordinatorError>;
+
+impl From<&CoordinatorError> for ErrorFrame {
+    fn from(error: &CoordinatorError) -> Self {
+        match error {
+            CoordinatorError::LobbyFull { .. } => {
+                Self::new(ErrorCode::LOBBY_FULL, error.to_string()).retryable()
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CoordinatorMsg {
+    /// Seat a new player; `outbox` receives every message addressed to them.
+    Join(
+        String,
+        DerivedActorRef<Message>,
+        RpcReplyPort<CoordinatorResult<PlayerId>>,
+    ),
+    /// A message sent by a seated player.
+    Inbound(PlayerId, Message),
+    /// A seated player's connection is gone; they are skipped from now on.
+    Leave(PlayerId),
+    StartGame,
+    NextRound,
+}
+
+pub struct CoordinatorArgs {
+    pub dealer: ActorRef<DealerMsg>,
+    pub score_manager: ActorRef<ScoreManagerMsg>,
+    pub rules: Box<dyn GameRules>,
+    pub seats: usize,
+    pub win_condition: usize,
+}
+
+#[actor(
+    msg = CoordinatorMsg,
+    state = CoordinatorState,
+    args = CoordinatorArgs,
+)]
+pub(crate) struct GameCoordinator;
+
+struct Seat {
+    id: PlayerId,
+    name: String,
+    outbox: DerivedActorRef<Message>,
+    hand: PlayerHand,
+    connected: bool,
+}
+
+enum Phase {
+    Lobby,
+    Submitting {
+        greens: Vec<GreenCard>,
+        reds: usize,
+        submitters: Vec<PlayerId>,
+        submissions: Vec<Submission>,
+    },
+    Picking {
+        greens: Vec<GreenCard>,
+        pickers: Vec<PlayerId>,
+        submissions: Vec<Submission>,
+        picks: Vec<(PlayerId, CardId)>,
+    },
+    Over,
+}
+
+pub(crate) struct CoordinatorState {
+    dealer: ActorRef<DealerMsg>,
+    score_manager: ActorRef<ScoreManagerMsg>,
+    rules: Box<dyn GameRules>,
+    seats: usize,
+    win_condition: usize,
+    players: Vec<Seat>,
+    judge: PlayerId,
+    phase: Phase,
+}
+
+impl CoordinatorState {
+    fn judge_id(&self) -> PlayerId {
+        self.judge
+    }
+
+    /// Players still connected, in seating order.
+    fn player_ids(&self) -> Vec<PlayerId> {
+        self.players
+            .iter()
+            .filter(|seat| seat.connected)
+            .map(|seat| seat.id)
+            .collect()
+    }
+
+    fn is_connected(&self, id: PlayerId) -> bool {
+        self.players
+            .iter()
+            .any(|seat| seat.id == id && seat.connected)
+    }
+
+    fn seat_mut(&mut self, id: PlayerId) -> Option<&mut Seat> {
+        self.players.iter_mut().find(|seat| seat.id == id)
+    }
+
+    fn send(&self, id: PlayerId, msg: Message) {
+        if let Some(seat) = self.players.iter().find(|seat| seat.id == id)
+            && let Err(e) = seat.outbox.send_message(msg)
+        {
+            tracing::warn!("Failed to reach {}: {e}", seat.id);
+        }
+    }
+
+    /// Tells a player their last message broke the rules.
+    fn reject(&self, id: PlayerId, reason: String) {
+        tracing::warn!("{id} {reason}");
+        let error = ErrorFrame::new(ErrorCode::ILLEGAL_MOVE, format!("You {reason}"));
+        self.send(id, Message::Error(error));
+    }
+
+    fn broadcast(&self, msg: Message) {
+        for seat in self.players.iter().filter(|seat| seat.connected) {
+            if let Err(e) = seat.outbox.send_message(msg.clone()) {
+                tracing::warn!("Failed to reach {}: {e}", seat.id);
+            }
+        }
+    }
+}
+
+impl GameCoordinator {
+    actor_pre_start!(Ok(CoordinatorState {
+        dealer: args.dealer,
+        score_manager: args.score_manager,
+        rules: args.rules,
+        seats: args.seats,
+        win_condition: args.win_condition,
+        players: Vec::with_capacity(args.seats),
+        judge: PlayerId(0),
+        phase: Phase::Lobby,
+    }));
+
+    actor_handle!({
+        match msg {
+            CoordinatorMsg::Join(name, outbox, reply) => {
+                if !matches!(state.phase, Phase::Lobby) || state.player_ids().len() >= state.seats {
+                    let _ = reply.send(Err(CoordinatorError::LobbyFull { seats: state.seats }));
+                    return Ok(());
+                }
+
+                // A seat left before the game started goes to the next player.
+                let id = state
+                    .players
+                    .iter()
+                    .find(|seat| !seat.connected)
+                    .map_or(PlayerId(state.players.len()), |seat| seat.id);
+                let seat = Seat {
+                    id,
+                    name,
+                    outbox,
+                    hand: PlayerHand::new(),
+                    connected: true,
+                };
+                match state.players.get_mut(id.0) {
+                    Some(empty) => *empty = seat,
+                    None => state.players.push(seat),
+                }
+                ractor::cast!(
+                    state.score_manager,
+                    ScoreManagerMsg::UpdateScore(id, Score(0))
+                )?;
+                let seated = state.player_ids().len();
+                tracing::info!("{id} joined ({seated}/{})", state.seats);
+
+                let _ = reply.send(Ok(id));
+                state.send(id, Message::Welcome { player_id: id });
+
+                if seated == state.seats {
+                    ractor::cast!(myself, CoordinatorMsg::StartGame)?;
+                }
+            }
+            CoordinatorMsg::StartGame => {
+                ractor::cast!(state.dealer, DealerMsg::Shuffle)?;
+                let hand_size = state.rules.hand_size();
+                for id in state.player_ids() {
+                    Self::deal_red(state, id, hand_size).await?;
+                }
+                ractor::cast!(myself, CoordinatorMsg::NextRound)?;
+            }
+            CoordinatorMsg::NextRound => {
+                // Players may have left while no round was running.
+                if state.player_ids().len() < 2 {
+                    tracing::info!("Not enough players left, ending the game");
+                    let winner = Self::leader(state).await?;
+                    Self::finish(state, &myself, winner);
+                    return Ok(());
+                }
+                let greens = if state.rules.blind_submissions() {
+                    tracing::info!("Red cards are played before the green card is drawn");
+                    Vec::new()
+                } else {
+                    match Self::draw_greens(state, &myself).await? {
+                        Some(greens) => greens,
+                        None => return Ok(()),
+                    }
+                };
+
+                let judge = state.judge_id();
+                let harvest = state.rules.harvest(&greens);
+                if harvest.redraw {
+                    tracing::info!("Bad harvest, every hand is redrawn");
+                    for id in state.player_ids() {
+                        Self::redraw(state, id).await?;
+                    }
+                }
+
+                let judge_plays = state.rules.judge_plays();
+                let submitters: Vec<PlayerId> = state
+                    .player_ids()
+                    .into_iter()
+                    .filter(|&id| judge_plays || id != judge)
+                    .collect();
+                let held = state
+                    .players
+                    .iter()
+                    .filter(|seat| submitters.contains(&seat.id))
+                    .map(|seat| seat.hand.len())
+                    .min()
+                    .unwrap_or(0);
+                let reds = harvest.reds.min(held).max(1);
+                for &id in &submitters {
+                    state.send(id, Message::PlayRequest { count: reds });
+                }
+                state.phase = Phase::Submitting {
+                    greens,
+                    reds,
+                    submissions: Vec::with_capacity(submitters.len()),
+                    submitters,
+                };
+            }
+            CoordinatorMsg::Inbound(id, Message::SubmitRed { cards }) => {
+                Self::submit(state, &myself, id, cards).await?;
+            }
+            CoordinatorMsg::Inbound(id, Message::JudgeChoice { card }) => {
+                Self::pick(state, &myself, id, card).await?;
+            }
+            CoordinatorMsg::Inbound(id, Message::Error(error)) => {
+                tracing::warn!("{id} passes: {error}");
+                Self::skip(state, &myself, id).await?;
+            }
+            CoordinatorMsg::Inbound(id, other) => {
+                tracing::warn!("Ignoring unexpected message from {id}: {other:?}");
+            }
+            CoordinatorMsg::Leave(id) => {
+                Self::leave(state, &myself, id).await?;
+            }
+        }
+        Ok(())
+    });
+}
+
+impl GameCoordinator {
+    async fn deal_red(
+        state: &mut CoordinatorState,
+        id: PlayerId,
+        amount: usize,
+    ) -> Result<(), ActorProcessingErr> {
+        let Some(amount) = NonZeroUsize::new(amount) else {
+            return Ok(());
+        };
+        let cards = match ractor::call!(state.dealer, DealerMsg::DealRedCards, amount)? {
+            Ok(cards) => cards,
+            Err(e) => {
+                tracing::warn!("Could not refill hand of {id}: {e}");
+                state.send(id, Message::Error((&e).into()));
+                return Ok(());
+            }
+        };
+
+        if let Some(seat) = state.seat_mut(id) {
+            cards
+                .iter()
+                .cloned()
+                .for_each(|card| seat.hand.add_card(card));
+        }
+        state.send(id, Message::DealHand { cards });
+        Ok(())
+    }
+
+    /// Deal and reveal this round's green cards, ending the game once the deck runs out.
+    async fn draw_greens(
+        state: &mut CoordinatorState,
+        myself: &ActorRef<CoordinatorMsg>,
+    ) -> Result<Option<Vec<GreenCard>>, ActorProcessingErr> {
+        let amount = NonZeroUsize::new(state.rules.greens_per_round()).unwrap_or(NonZeroUsize::MIN);
+        let greens = match ractor::call!(state.dealer, DealerMsg::DealGreenCards, amount)? {
+            Ok(cards) => cards,
+            Err(DealError::OutOfCards { .. }) => {
+                tracing::info!("Green deck exhausted, ending the game");
+                let winner = Self::leader(state).await?;
+                Self::finish(state, myself, winner);
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let judge = state.judge_id();
+        let names: Vec<&str> = greens.iter().map(|green| green.name()).collect();
+        tracing::info!("{judge} judges {}", names.join(" and "));
+        state.broadcast(Self::reveal(judge, &greens)?);
+        Ok(Some(greens))
+    }
+
+    fn reveal(judge: PlayerId, greens: &[GreenCard]) -> Result<Message, ActorProcessingErr> {
+        match greens {
+            [card] => Ok(Message::RevealGreen {
+                judge,
+                card: card.clone(),
+            }),
+            [first, second] => Ok(Message::RevealGreenPair {
+                judge,
+                first: first.clone(),
+                second: second.clone(),
+            }),
+            _ => Err(ActorProcessingErr::from(format!(
+                "cannot reveal {} green cards",
+                greens.len()
+            ))),
+        }
+    }
+
+    async fn redraw(state: &mut CoordinatorState, id: PlayerId) -> Result<(), ActorProcessingErr> {
+        let Some(seat) = state.seat_mut(id) else {
+            return Ok(());
+        };
+        let discarded = std::mem::take(&mut seat.hand).get_cards().to_vec();
+        let cards = match ractor::call!(state.dealer, DealerMsg::Redraw, discarded.clone())? {
+            Ok(cards) => cards,
+            Err(e) => {
+                tracing::warn!("Could not redraw the hand of {id}: {e}");
+                discarded
+            }
+        };
+
+        if let Some(seat) = state.seat_mut(id) {
+            cards
+                .iter()
+                .cloned()
+                .for_each(|card| seat.hand.add_card(card));
+        }
+        state.send(id, Message::ReplaceHand { cards });
+        Ok(())
+    }
+
+    async fn submit(
+        state: &mut CoordinatorState,
+        myself: &ActorRef<CoordinatorMsg>,
+        id: PlayerId,
+        cards: Vec<RedCard>,
+    ) -> Result<(), ActorProcessingErr> {
+        let Phase::Submitting {
+            reds,
+            submitters,
+            submissions,
+            ..
+        } = &mut state.phase
+        else {
+            state.reject(id, "submitted outside of the submission phase".into());
+            return Ok(());
+        };
+        if !submitters.contains(&id) || submissions.iter().any(|(from, _)| *from == id) {
+            state.reject(id, "are not allowed to submit".into());
+            return Ok(());
+        }
+
+        if cards.len() != *reds {
+            let reason = format!("submitted {} cards instead of {reds}", cards.len());
+            state.reject(id, reason);
+            return Ok(());
+        }
+        if cards
+            .iter()
+            .enumerate()
+            .any(|(i, card)| cards[..i].iter().any(|other| other.id() == card.id()))
+        {
+            state.reject(id, "submitted the same card twice".into());
+            return Ok(());
+        }
+
+        let Some(seat) = state.players.iter_mut().find(|seat| seat.id == id) else {
+            return Ok(());
+        };
+        if !cards
+            .iter()
+            .all(|card| seat.hand.get_cards().contains(card))
+        {
+            state.reject(id, "submitted a card that is not in your hand".into());
+            return Ok(());
+        }
+        let played = cards
+            .iter()
+            .filter_map(|card| {
+                let index = seat.hand.get_cards().iter().position(|c| c == card)?;
+                seat.hand.remove_card(index)
+            })
+            .collect();
+        submissions.push((id, played));
+        let waiting = submissions.len() < submitters.len();
+        state.send(id, Message::Submitted { cards });
+
+        if waiting {
+            return Ok(());
+        }
+        Self::request_picks(state, myself).await
+    }
+
+    /// Every submission is in: reveal any missing greens and ask for picks.
+    async fn request_picks(
+        state: &mut CoordinatorState,
+        myself: &ActorRef<CoordinatorMsg>,
+    ) -> Result<(), ActorProcessingErr> {
+        let Phase::Submitting {
+            mut greens,
+            mut submissions,
+            ..
+        } = std::mem::replace(&mut state.phase, Phase::Over)
+        else {
+            return Ok(());
+        };
+        if submissions.is_empty() {
+            return Self::void_round(state, myself, greens, submissions);
+        }
+        submissions.shuffle(&mut rand::thread_rng());
+        if greens.is_empty() {
+            greens = match Self::draw_greens(state, myself).await? {
+                Some(greens) => greens,
+                None => return Ok(()),
+            };
+        }
+
+        let worst = state.rules.pick_worst();
+        let pickers: Vec<PlayerId> = state
+            .rules
+            .pickers(state.judge_id(), &state.player_ids())
+            .into_iter()
+            .filter(|&picker| state.is_connected(picker))
+            .collect();
+        if pickers.is_empty() {
+            return Self::void_round(state, myself, greens, submissions);
+        }
+        let cards: Vec<Vec<RedCard>> = submissions.iter().map(|(_, cards)| cards.clone()).collect();
+        for &picker in &pickers {
+            state.send(
+                picker,
+                Message::JudgeRequest {
+                    submissions: cards.clone(),
+                    worst,
+                },
+            );
+        }
+        state.phase = Phase::Picking {
+            greens,
+            picks: Vec::with_capacity(pickers.len()),
+            pickers,
+            submissions,
+        };
+        Ok(())
+    }
+
+    async fn pick(
+        state: &mut CoordinatorState,
+        myself: &ActorRef<CoordinatorMsg>,
+        id: PlayerId,
+        choice: CardId,
+    ) -> Result<(), ActorProcessingErr> {
+        let Phase::Picking {
+            pickers,
+            submissions,
+            picks,
+            ..
+        } = &mut state.phase
+        else {
+            state.reject(id, "picked outside of the picking phase".into());
+            return Ok(());
+        };
+        if !pickers.contains(&id) || picks.iter().any(|(picker, _)| *picker == id) {
+            state.reject(id, "are not allowed to pick this round".into());
+            return Ok(());
+        }
+        if rules::submission_of(submissions, choice).is_none() {
+            state.reject(id, "picked a card that was not submitted".into());
+            return Ok(());
+        }
+        picks.push((id, choice));
+        if picks.len() < pickers.len() {
+            return Ok(());
+        }
+        Self::score_round(state, myself).await
+    }
+
+    /// Every pick is in: score the round, then end the game or start the next one.
+    async fn score_round(
+        state: &mut CoordinatorState,
+        myself: &ActorRef<CoordinatorMsg>,
+    ) -> Result<(), ActorProcessingErr> {
+        let Phase::Picking {
+            greens,
+            submissions,
+            picks,
+            ..
+        } = std::mem::replace(&mut state.phase, Phase::Over)
+        else {
+            return Ok(());
+        };
+        if picks.is_empty() {
+            return Self::void_round(state, myself, greens, submissions);
+        }
+        let Some(outcome) = state.rules.score(state.judge_id(), &submissions, &picks) else {
+            return Self::void_round(state, myself, greens, submissions);
+        };
+        let (_, reds) = rules::submission_of(&submissions, outcome.red)
+            .cloned()
+            .ok_or_else(|| ActorProcessingErr::from("decisive card was not submitted"))?;
+        let names: Vec<&str> = reds.iter().map(|card| card.name()).collect();
+
+        for &winner in &outcome.winners {
+            let Score(score) =
+                ractor::call!(state.score_manager, ScoreManagerMsg::RetrieveScore, winner)??;
+            let score = score + greens.len();
+            ractor::cast!(
+                state.score_manager,
+                ScoreManagerMsg::UpdateScore(winner, Score(score))
+            )?;
+        }
+        tracing::info!(
+            "{:?} win the round with {}",
+            outcome.winners,
+            names.join(" + ")
+        );
+
+        state.broadcast(Message::RoundResult {
+            winners: outcome.winners.clone(),
+            greens,
+            reds,
+        });
+        let scores = Self::scores(state).await?;
+        let winner = state.rules.winner(&scores, state.win_condition);
+        state.broadcast(Message::ScoreUpdate { scores });
+
+        if let Some(winner) = winner {
+            Self::finish(state, myself, winner);
+            return Ok(());
+        }
+
+        let hand_size = state.rules.hand_size();
+        let refills: Vec<(PlayerId, usize)> = state
+            .players
+            .iter()
+            .filter(|seat| seat.connected && seat.hand.len() < hand_size)
+            .map(|seat| (seat.id, hand_size - seat.hand.len()))
+            .collect();
+        for (refill, missing) in refills {
+            Self::deal_red(state, refill, missing).await?;
+        }
+
+        Self::advance_judge(state, &outcome.winners);
+        ractor::cast!(myself, CoordinatorMsg::NextRound)?;
+        Ok(())
+    }
+
+    /// Hand the judge role on, skipping players who left.
+    fn advance_judge(state: &mut CoordinatorState, winners: &[PlayerId]) {
+        let seats: Vec<PlayerId> = state.players.iter().map(|seat| seat.id).collect();
+        let mut judge = state.rules.next_judge(state.judge, &seats, winners);
+        for _ in 0..seats.len() {
+            if state.is_connected(judge) {
+                break;
+            }
+            judge = state.rules.next_judge(judge, &seats, &[]);
+        }
+        state.judge = judge;
+    }
+
+    /// Drop a departed player from the round in progress, moving the round on
+    /// if everyone left in it already acted.
+    async fn leave(
+        state: &mut CoordinatorState,
+        myself: &ActorRef<CoordinatorMsg>,
+        id: PlayerId,
+    ) -> Result<(), ActorProcessingErr> {
+        let Some(seat) = state.seat_mut(id) else {
+            return Ok(());
+        };
+        seat.connected = false;
+        tracing::warn!("{id} left the game");
+
+        if matches!(state.phase, Phase::Lobby | Phase::Over) {
+            return Ok(());
+        }
+        if state.player_ids().len() < 2 {
+            tracing::info!("Not enough players left, ending the game");
+            let winner = Self::leader(state).await?;
+            Self::finish(state, myself, winner);
+            return Ok(());
+        }
+        Self::skip(state, myself, id).await
+    }
+
+    /// Stop waiting for `id` this round, moving on once everyone else is done.
+    async fn skip(
+        state: &mut CoordinatorState,
+        myself: &ActorRef<CoordinatorMsg>,
+        id: PlayerId,
+    ) -> Result<(), ActorProcessingErr> {
+        match &mut state.phase {
+            Phase::Submitting {
+                submitters,
+                submissions,
+                ..
+            } => {
+                if submissions.iter().any(|(from, _)| *from == id) {
+                    return Ok(());
+                }
+                submitters.retain(|&submitter| submitter != id);
+                if submissions.len() >= submitters.len() {
+                    Self::request_picks(state, myself).await?;
+                }
+            }
+            Phase::Picking { pickers, picks, .. } => {
+                if picks.iter().any(|(from, _)| *from == id) {
+                    return Ok(());
+                }
+                pickers.retain(|&picker| picker != id);
+                if picks.len() >= pickers.len() {
+                    Self::score_round(state, myself).await?;
+                }
+            }
+            Phase::Lobby | Phase::Over => {}
+        }
+        Ok(())
+    }
+
+    /// Abandon a round nobody can finish: submitted cards go back to their
+    /// owners, everyone learns nobody scores and the next judge starts a new
+    /// round.
+    fn void_round(
+        state: &mut CoordinatorState,
+        myself: &ActorRef<CoordinatorMsg>,
+        greens: Vec<GreenCard>,
+        submissions: Vec<Submission>,
+    ) -> Result<(), ActorProcessingErr> {
+        tracing::info!("Nobody is left to finish the round, starting a new one");
+        for (id, cards) in submissions {
+            if !state.is_connected(id) {
+                continue;
+            }
+            if let Some(seat) = state.seat_mut(id) {
+                cards
+                    .iter()
+                    .cloned()
+                    .for_each(|card| seat.hand.add_card(card));
+            }
+            state.send(id, Message::DealHand { cards });
+        }
+        state.broadcast(Message::RoundResult {
+            winners: Vec::new(),
+            greens,
+            reds: Vec::new(),
+        });
+        state.phase = Phase::Over;
+        Self::advance_judge(state, &[]);
+        ractor::cast!(myself, CoordinatorMsg::NextRound)?;
+        Ok(())
+    }
+
+    async fn scores(
+        state: &CoordinatorState,
+    ) -> Result<Vec<(PlayerId, usize)>, ActorProcessingErr> {
+        let mut scores = Vec::with_capacity(state.players.len());
+        for seat in &state.players {
+            let Score(score) =
+                ractor::call!(state.score_manager, ScoreManagerMsg::RetrieveScore, seat.id)??;
+            scores.push((seat.id, score));
+        }
+        Ok(scores)
+    }
+
+    /// The connected player with the highest score.
+    async fn leader(state: &CoordinatorState) -> Result<PlayerId, ActorProcessingErr> {
+        let scores = Self::scores(state).await?;
+        Ok(scores
+            .into_iter()
+            .filter(|&(id, _)| state.is_connected(id))
+            .max_by_key(|&(_, score)| score)
+            .map_or(state.judge_id(), |(id, _)| id))
+    }
+
+    fn finish(state: &mut CoordinatorState, myself: &ActorRef<CoordinatorMsg>, winner: PlayerId) {
+        let name = state
+            .players
+            .iter()
+            .find(|seat| seat.id == winner)
+            .map_or("unknown", |seat| seat.name.as_str());
+        tracing::info!("Game over, {winner} ({name}) wins");
+        state.broadcast(Message::GameOver { winner });
+        state.phase = Phase::Over;
+        myself.stop(Some("game over".into()));
+    }
+}
@@ -0,0 +1,226 @@
+use crate::actors::{
+    connection::{Connection, ConnectionArgs, ConnectionEvent, ConnectionMsg, FramedTransport},
+    game_coordinator::CoordinatorMsg,
+};
+use crate::transport::Transport;
+use ::ractor::{Actor, ActorId, ActorProcessingErr, ActorRef, RpcReplyPort, SupervisionEvent};
+use ahash::AHashMap as Map;
+use apples_core::PlayerId;
+use apples_protocol::{CodecMetrics, ErrorCode, ErrorFrame, Message, Metrics, PayloadFormat};
+use apples_utils::config::Heartbeat;
+use std::{sync::Arc, time::Duration};
+
+/// How long a connection gets to flush pending frames on shutdown.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum LobbyMsg {
+    /// Take ownership of a socket that completed the version handshake,
+    /// along with the payload format it settled on.
+    Accept(FramedTransport, PayloadFormat),
+    Connection(ConnectionEvent),
+    /// Report the traffic of every open connection.
+    Status(RpcReplyPort<Vec<ConnectionStats>>),
+    /// Flush and close every connection.
+    Shutdown(RpcReplyPort<()>),
+}
+
+/// Codec counters of one connection, for the host's status output.
+#[derive(Debug, Clone)]
+pub struct ConnectionStats {
+    pub peer: String,
+    pub player: Option<PlayerId>,
+    pub metrics: Metrics,
+}
+
+impl From<ConnectionEvent> for LobbyMsg {
+    #[inline]
+    fn from(event: ConnectionEvent) -> Self {
+        Self::Connection(event)
+    }
+}
+
+impl TryFrom<LobbyMsg> for ConnectionEvent {
+    type Error = LobbyMsg;
+
+    #[inline]
+    fn try_from(msg: LobbyMsg) -> Result<Self, LobbyMsg> {
+        match msg {
+            LobbyMsg::Connection(event) => Ok(event),
+            other => Err(other),
+        }
+    }
+}
+
+/// Maps connections to seated players and relays their messages to the coordinator.
+///
+/// Connections are linked to the lobby, so one that fails without a chance
+/// to report its disconnect still frees its seat.
+pub(crate) struct Lobby;
+
+struct Peer {
+    connection: ActorRef<ConnectionMsg>,
+    player: Option<PlayerId>,
+    format: PayloadFormat,
+    address: String,
+    metrics: Arc<CodecMetrics>,
+}
+
+impl Peer {
+    fn stats(&self) -> ConnectionStats {
+        ConnectionStats {
+            peer: self.address.clone(),
+            player: self.player,
+            metrics: self.metrics.snapshot(),
+        }
+    }
+}
+
+pub(crate) struct LobbyState {
+    coordinator: ActorRef<CoordinatorMsg>,
+    heartbeat: Heartbeat,
+    peers: Map<ActorId, Peer>,
+}
+
+impl LobbyState {
+    /// Forgets the connection `id` and gives up its seat, if it had one.
+    fn disconnect(&mut self, id: ActorId, reason: Option<&str>) -> Result<(), ActorProcessingErr> {
+        let Some(peer) = self.peers.remove(&id) else {
+            return Ok(());
+        };
+        let who = peer
+            .player
+            .map_or(id.to_string(), |player| player.to_string());
+        tracing::warn!("{who} disconnected: {}", reason.unwrap_or("closed"));
+        tracing::info!("{who} at {}: {}", peer.address, peer.metrics.snapshot());
+        if let Some(player) = peer.player {
+            ractor::cast!(self.coordinator, CoordinatorMsg::Leave(player))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "async-trait", ractor::async_trait)]
+impl Actor for Lobby {
+    type Msg = LobbyMsg;
+    type State = LobbyState;
+    type Arguments = (ActorRef<CoordinatorMsg>, Heartbeat);
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        let (coordinator, heartbeat) = args;
+        Ok(LobbyState {
+            coordinator,
+            heartbeat,
+            peers: Map::new(),
+        })
+    }
+
+    async fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        msg: Self::Msg,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        match msg {
+            LobbyMsg::Accept(stream, format) => {
+                let address = stream.get_ref().peer();
+                let metrics = stream.codec().metrics().cloned().unwrap_or_default();
+                let args = ConnectionArgs {
+                    stream,
+                    parent: myself.get_derived(),
+                    heartbeat: state.heartbeat,
+                    format,
+                };
+                let (connection, _) =
+                    Actor::spawn_linked(None, Connection, args, myself.get_cell()).await?;
+                state.peers.insert(
+                    connection.get_id(),
+                    Peer {
+                        connection,
+                        player: None,
+                        format,
+                        address,
+                        metrics,
+                    },
+                );
+            }
+            LobbyMsg::Connection(ConnectionEvent::Frame(id, frame)) => {
+                let Some(peer) = state.peers.get_mut(&id) else {
+                    return Ok(());
+                };
+                let msg = match Message::decode_with(&frame, &peer.format) {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        tracing::warn!("Dropping undecodable frame from {id}: {e}");
+                        let error = ErrorFrame::from(&e).with_correlation(frame.correlation());
+                        let _ = peer.connection.cast(Message::Error(error).into());
+                        return Ok(());
+                    }
+                };
+
+                match (peer.player, msg) {
+                    (None, Message::JoinRequest { name }) => {
+                        let outbox = peer.connection.get_derived();
+                        match ractor::call!(state.coordinator, CoordinatorMsg::Join, name, outbox)?
+                        {
+                            Ok(player) => peer.player = Some(player),
+                            Err(e) => {
+                                tracing::warn!("Rejecting connection {id}: {e}");
+                                let _ = peer.connection.cast(Message::Error((&e).into()).into());
+                                let _ = peer.connection.cast(ConnectionMsg::Close);
+                                state.peers.remove(&id);
+                            }
+                        }
+                    }
+                    (Some(player), msg) => {
+                        ractor::cast!(state.coordinator, CoordinatorMsg::Inbound(player, msg))?;
+                    }
+                    (None, other) => {
+                        tracing::warn!("Connection {id} sent {other:?} before joining");
+                        let error = ErrorFrame::new(ErrorCode::ILLEGAL_MOVE, "Join the game first")
+                            .with_correlation(frame.correlation());
+                        let _ = peer.connection.cast(Message::Error(error).into());
+                    }
+                }
+            }
+            LobbyMsg::Connection(ConnectionEvent::Disconnected(id, reason)) => {
+                state.disconnect(id, reason.as_deref())?;
+            }
+            LobbyMsg::Status(reply) => {
+                let _ = reply.send(state.peers.values().map(Peer::stats).collect());
+            }
+            LobbyMsg::Shutdown(reply) => {
+                for (_, peer) in state.peers.drain() {
+                    let _ = peer.connection.drain_and_wait(Some(DRAIN_TIMEOUT)).await;
+                }
+                let _ = reply.send(());
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_supervisor_evt(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        event: SupervisionEvent,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        match event {
+            // A failed connection skips `post_stop`, so it never reported its disconnect.
+            SupervisionEvent::ActorFailed(cell, reason) => {
+                state.disconnect(cell.get_id(), Some(&reason.to_string()))?;
+            }
+            // So does a killed one, which is the only kind that leaves no state.
+            SupervisionEvent::ActorTerminated(cell, None, reason) => {
+                state.disconnect(cell.get_id(), reason.as_deref())?;
+            }
+            // Any other connection reported its disconnect from `post_stop`.
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
+pub(crate) mod bot_player;
 pub(crate) mod client_player;
 pub(crate) mod connection;
 pub(crate) mod dealer;
+pub(crate) mod game_coordinator;
+pub(crate) mod lobby;
 pub(crate) mod score_handler;
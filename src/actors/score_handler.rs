@@ -1,8 +1,8 @@
-use ::ractor::{ActorProcessingErr, RpcReplyPort};
+use ::ractor::RpcReplyPort;
 use ahash::AHashMap as Map;
 use anyhow::Result;
 use apples_core::player::player::PlayerId;
-use core::num::NonZeroUsize;
+use apples_protocol::{ErrorCode, ErrorFrame};
 use dsl_ractor::{actor, actor_handle, actor_pre_start};
 use thiserror::Error;
 
@@ -15,6 +15,16 @@ pub enum ScoreError {
 
 pub type ScoreResult = Result<Score, ScoreError>;
 
+impl From<&ScoreError> for ErrorFrame {
+    fn from(error: &ScoreError) -> Self {
+        match error {
+            ScoreError::PlayerNotFound { .. } => {
+                Self::new(ErrorCode::PLAYER_NOT_FOUND, error.to_string())
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ScoreManagerMsg {
     UpdateScore(PlayerId, Score),
@@ -0,0 +1,62 @@
+use {
+    super::{
+        keyword::{best_match, green_keywords, overlap, red_keywords, worst_match},
+        strategy::BotStrategy,
+    },
+    ahash::{AHashMap as Map, AHashSet as Set},
+    apples_core::{GreenCard, PlayerId, RedCard},
+};
+
+/// Learns which words every judge tends to reward and plays to their taste.
+///
+/// Falls back to keyword overlap with the green card for judges it has not seen yet.
+#[derive(Debug, Default, Clone)]
+pub struct JudgeModelStrategy {
+    judge: Option<PlayerId>,
+    taste: Map<PlayerId, Map<String, usize>>,
+}
+
+impl JudgeModelStrategy {
+    fn score(&self, keywords: &Set<String>, red: &RedCard) -> usize {
+        let learned = self
+            .judge
+            .and_then(|judge| self.taste.get(&judge))
+            .map_or(0, |taste| {
+                red_keywords(red)
+                    .iter()
+                    .filter_map(|stem| taste.get(stem))
+                    .sum()
+            });
+        learned + overlap(keywords, red)
+    }
+}
+
+impl BotStrategy for JudgeModelStrategy {
+    fn reveal(&mut self, judge: PlayerId, _greens: &[GreenCard]) {
+        self.judge = Some(judge);
+    }
+
+    fn choose_red(&mut self, greens: &[GreenCard], hand: &[RedCard]) -> usize {
+        let keywords = green_keywords(greens);
+        hand.iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, card)| self.score(&keywords, card))
+            .map_or(0, |(index, _)| index)
+    }
+
+    fn judge(&mut self, greens: &[GreenCard], submissions: &[RedCard]) -> usize {
+        best_match(&green_keywords(greens), submissions)
+    }
+
+    fn judge_worst(&mut self, greens: &[GreenCard], submissions: &[RedCard]) -> usize {
+        worst_match(&green_keywords(greens), submissions)
+    }
+
+    fn observe(&mut self, judge: PlayerId, _greens: &[GreenCard], winner: &RedCard) {
+        let taste = self.taste.entry(judge).or_default();
+        for stem in red_keywords(winner) {
+            *taste.entry(stem).or_default() += 1;
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use {
+    super::strategy::BotStrategy,
+    ahash::AHashSet as Set,
+    apples_core::{cards::card::Card, GreenCard, RedCard},
+};
+
+/// Words shorter than this carry too little meaning to compare.
+const MIN_WORD_LEN: usize = 4;
+/// Words are compared on their prefix so "charming" matches "charm".
+const STEM_LEN: usize = 5;
+
+/// Plays the red card whose text shares the most words with the green card's synonyms.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct KeywordStrategy;
+
+impl BotStrategy for KeywordStrategy {
+    fn choose_red(&mut self, greens: &[GreenCard], hand: &[RedCard]) -> usize {
+        best_match(&green_keywords(greens), hand)
+    }
+
+    fn judge(&mut self, greens: &[GreenCard], submissions: &[RedCard]) -> usize {
+        best_match(&green_keywords(greens), submissions)
+    }
+
+    fn judge_worst(&mut self, greens: &[GreenCard], submissions: &[RedCard]) -> usize {
+        worst_match(&green_keywords(greens), submissions)
+    }
+}
+
+/// Stems of the green cards' names and the synonyms listed in their descriptions.
+pub fn green_keywords(greens: &[GreenCard]) -> Set<String> {
+    greens
+        .iter()
+        .flat_map(|green| stems(green.name()).chain(stems(green.description())))
+        .collect()
+}
+
+/// Stems of the red card's name and flavour text.
+pub fn red_keywords(red: &RedCard) -> Set<String> {
+    stems(red.name()).chain(stems(red.description())).collect()
+}
+
+/// Amount of stems `red` shares with `keywords`.
+pub fn overlap(keywords: &Set<String>, red: &RedCard) -> usize {
+    red_keywords(red).intersection(keywords).count()
+}
+
+/// Index of the card with the highest overlap, the first one on ties.
+pub fn best_match(keywords: &Set<String>, cards: &[RedCard]) -> usize {
+    cards
+        .iter()
+        .enumerate()
+        .rev()
+        .max_by_key(|(_, card)| overlap(keywords, card))
+        .map_or(0, |(index, _)| index)
+}
+
+/// Index of the card with the lowest overlap, the first one on ties.
+pub fn worst_match(keywords: &Set<String>, cards: &[RedCard]) -> usize {
+    cards
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, card)| overlap(keywords, card))
+        .map_or(0, |(index, _)| index)
+}
+
+fn stems(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_WORD_LEN)
+        .map(|word| word.to_lowercase().chars().take(STEM_LEN).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::KeywordStrategy,
+        crate::bots::BotStrategy,
+        apples_core::{GreenCard, RedCard},
+    };
+
+    #[test]
+    fn prefers_overlapping_text() {
+        let greens = [GreenCard::new(
+            0usize,
+            "Charming",
+            "(captivating, enchanting, fascinating)",
+        )];
+        let hand = [
+            RedCard::new(0usize, "Mud", "Wet dirt, found after the rain."),
+            RedCard::new(1usize, "Unicorns", "Enchanted horses with a single horn."),
+            RedCard::new(2usize, "Taxes", "Nobody escapes them."),
+        ];
+
+        assert_eq!(KeywordStrategy.choose_red(&greens, &hand), 1);
+        assert_eq!(KeywordStrategy.judge(&greens, &hand[..1]), 0);
+        assert_eq!(KeywordStrategy.judge_worst(&greens, &hand), 0);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod judge_model;
+pub mod keyword;
+pub mod random;
+pub mod strategy;
+
+pub use {
+    judge_model::JudgeModelStrategy, keyword::KeywordStrategy, random::RandomStrategy,
+    strategy::BotStrategy,
+};
@@ -0,0 +1,23 @@
+use {
+    super::strategy::BotStrategy,
+    apples_core::{GreenCard, RedCard},
+    rand::Rng,
+};
+
+/// Plays and judges uniformly at random.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RandomStrategy;
+
+impl BotStrategy for RandomStrategy {
+    fn choose_red(&mut self, _greens: &[GreenCard], hand: &[RedCard]) -> usize {
+        rand::thread_rng().gen_range(0..hand.len())
+    }
+
+    fn judge(&mut self, _greens: &[GreenCard], submissions: &[RedCard]) -> usize {
+        rand::thread_rng().gen_range(0..submissions.len())
+    }
+
+    fn judge_worst(&mut self, greens: &[GreenCard], submissions: &[RedCard]) -> usize {
+        self.judge(greens, submissions)
+    }
+}
@@ -0,0 +1,29 @@
+use apples_core::{GreenCard, PlayerId, RedCard};
+use apples_utils::bot_kind::BotKind;
+
+/// Decision making for a bot seat; indices always point into the given slice.
+pub trait BotStrategy: Send + 'static {
+    /// Called when the green cards are revealed, before any card is chosen.
+    fn reveal(&mut self, _judge: PlayerId, _greens: &[GreenCard]) {}
+
+    /// Pick the red card from `hand` to play against `greens`.
+    fn choose_red(&mut self, greens: &[GreenCard], hand: &[RedCard]) -> usize;
+
+    /// Pick the winning red card among `submissions` while judging `greens`.
+    fn judge(&mut self, greens: &[GreenCard], submissions: &[RedCard]) -> usize;
+
+    /// Pick the losing red card among `submissions` while judging `greens`.
+    fn judge_worst(&mut self, greens: &[GreenCard], submissions: &[RedCard]) -> usize;
+
+    /// Called after every round with the judge and the cards they picked.
+    fn observe(&mut self, _judge: PlayerId, _greens: &[GreenCard], _winner: &RedCard) {}
+}
+
+/// Create the strategy configured for a bot.
+pub fn from_kind(kind: BotKind) -> Box<dyn BotStrategy> {
+    match kind {
+        BotKind::Random => Box::new(super::RandomStrategy),
+        BotKind::Keyword => Box::new(super::KeywordStrategy),
+        BotKind::JudgeModel => Box::new(super::JudgeModelStrategy::default()),
+    }
+}
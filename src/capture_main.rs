@@ -0,0 +1,88 @@
+use anyhow::{bail, Context, Result};
+use apples_protocol::{CaptureReader, Direction, Frame, FrameCodec, Inspector, Kind, Record};
+use apples_utils::cli::CaptureCommand;
+use bytes::BytesMut;
+use std::{collections::HashMap, path::Path, time::Duration};
+use tokio_util::codec::{Decoder, Encoder};
+
+#[doc = include_str!("../doc/capture.md")]
+pub async fn capture_main(command: CaptureCommand) -> Result<()> {
+    match command {
+        CaptureCommand::Print { path } => show(&path, None, None).await,
+        CaptureCommand::Replay {
+            path,
+            stream,
+            speed,
+        } => {
+            if !speed.is_finite() || speed <= 0.0 {
+                bail!("speed must be positive, got {speed}");
+            }
+            show(&path, stream, Some(speed)).await
+        }
+    }
+}
+
+/// Prints the records of a capture. When `speed` is set, every frame is
+/// replayed through a codec and printed at its original spacing.
+async fn show(path: &Path, stream: Option<u32>, speed: Option<f64>) -> Result<()> {
+    let mut inspector = Inspector::new();
+    let mut codecs = HashMap::new();
+    let mut first = None;
+    let mut previous = None;
+    for record in CaptureReader::open(path)? {
+        let record = record?;
+        // Every stream is inspected so each keeps track of its payload format.
+        let description = match speed {
+            None => inspector.describe(&record),
+            Some(_) => match replay(&mut codecs, &record) {
+                Ok(frame) => inspector.describe_frame(record.stream, &frame),
+                Err(e) => format!("failed to replay: {e}"),
+            },
+        };
+        if stream.is_some_and(|stream| stream != record.stream) {
+            continue;
+        }
+
+        if let (Some(speed), Some(previous)) = (speed, previous) {
+            let gap = record.at_micros.saturating_sub(previous) as f64 / speed;
+            tokio::time::sleep(Duration::from_micros(gap as u64)).await;
+        }
+        previous = Some(record.at_micros);
+        let first = *first.get_or_insert(record.at_micros);
+        println!("{} {description}", prefix(&record, first));
+    }
+    Ok(())
+}
+
+/// Encodes the frame of `record` and decodes it again with the codec of its
+/// stream, as if it crossed the wire once more.
+fn replay(codecs: &mut HashMap<u32, FrameCodec>, record: &Record) -> Result<Frame> {
+    let codec = codecs.entry(record.stream).or_default();
+    let mut wire = BytesMut::new();
+    codec.encode(record.frame()?, &mut wire)?;
+    codec
+        .decode(&mut wire)?
+        .context("the codec held back part of the frame")
+}
+
+/// Time since the first record, stream, direction, kind and correlation ID.
+fn prefix(record: &Record, first: u64) -> String {
+    let elapsed = Duration::from_micros(record.at_micros.saturating_sub(first));
+    let arrow = match record.direction {
+        Direction::Inbound => "<-",
+        Direction::Outbound => "->",
+    };
+    let kind = Kind::try_from(record.kind).map_or_else(
+        |_| format!("kind {}", record.kind),
+        |kind| format!("{kind:?}"),
+    );
+    let correlation = record
+        .correlation
+        .map(|id| format!(" {id}"))
+        .unwrap_or_default();
+    format!(
+        "{:>10.6}s [{}] {arrow} {kind}{correlation}",
+        elapsed.as_secs_f64(),
+        record.stream
+    )
+}
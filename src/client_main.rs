@@ -1,10 +1,94 @@
-use anyhow::Result;
-use std::net::SocketAddrV4;
-use tokio::net::TcpStream;
+use crate::actors::client_player::{ClientPlayer, PlayerMsg};
+use crate::tls;
+use crate::transport::Endpoint;
+use anyhow::{bail, Result};
+use apples_protocol::{handshake, Capture, FrameCodec, Hello, Message};
+use apples_utils::{config::Config, consts::CONFIG_TOML};
+use futures_util::{SinkExt, StreamExt};
+use ractor::Actor;
+use std::path::PathBuf;
+use tokio::sync::mpsc::unbounded_channel;
+use tokio_util::codec::Framed;
 
-#[doc = "client.md"]
-pub async fn client_main(socket: SocketAddrV4) -> Result<()> {
-    let stream = TcpStream::connect(socket).await?;
+#[doc = include_str!("../doc/client.md")]
+pub async fn client_main(endpoint: Endpoint, name: String, capture: Option<PathBuf>) -> Result<()> {
+    let config = Config::parse_config(CONFIG_TOML.into())?;
+    let heartbeat = config.heartbeat();
+    let preferred = config.payload_format();
+    let stream = endpoint.connect().await?;
+    let stream = if config.tls().enabled() {
+        tls::connect(config.tls(), stream).await?
+    } else {
+        stream
+    };
+    let capture = capture.map(Capture::create).transpose()?;
+    let mut codec = FrameCodec::with_default_limit();
+    codec.set_capture(capture.clone());
+    let mut framed = Framed::new(stream, codec);
 
+    let hello = match preferred {
+        Some(format) => Hello::default().prefer(format),
+        None => Hello::default(),
+    };
+    let session = handshake::initiate(&mut framed, hello).await?;
+    let format = session.format;
+    tracing::debug!(
+        "Negotiated protocol version {} with {format:?} payloads",
+        session.version
+    );
+
+    framed
+        .send(Message::JoinRequest { name }.encode_with(&format)?)
+        .await?;
+    let id = match framed.next().await.transpose()? {
+        Some(frame) => match Message::decode_with(&frame, &format)? {
+            Message::Welcome { player_id } => player_id,
+            other => bail!("expected a welcome from the host, got {other:?}"),
+        },
+        None => bail!("host closed the connection during the handshake"),
+    };
+    println!("Joined the game as {id}, waiting for the other players...");
+
+    let (mut sink, mut stream) = framed.split();
+    let (outbox, mut outgoing) = unbounded_channel();
+    let writer = tokio::spawn(async move {
+        while let Some(frame) = outgoing.recv().await {
+            sink.send(frame).await?;
+        }
+        sink.close().await
+    });
+    let (player, player_handle) =
+        Actor::spawn(None, ClientPlayer, (id, outbox.clone(), format)).await?;
+
+    // Pings are answered here because the player blocks on stdin while choosing.
+    let idle_timeout = heartbeat.idle_timeout();
+    loop {
+        let Ok(next) = tokio::time::timeout(idle_timeout, stream.next()).await else {
+            println!("The host stopped responding");
+            break;
+        };
+        let Some(frame) = next else {
+            break;
+        };
+        match Message::decode_with(&frame?, &format)? {
+            Message::Ping => {
+                let _ = outbox.send(Message::Pong.encode_with(&format)?);
+            }
+            Message::Pong => {}
+            msg => {
+                if player.cast(PlayerMsg::Server(msg)).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    let _ = player.drain();
+    player_handle.await?;
+    drop(outbox);
+    writer.await??;
+    if let Some(capture) = capture {
+        tokio::task::spawn_blocking(move || capture.flush()).await??;
+    }
     Ok(())
 }
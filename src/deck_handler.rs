@@ -33,6 +33,14 @@ impl DeckHandler {
         self.green_deck.add_card(card);
     }
 
+    /// Put discarded red cards under the red deck
+    #[inline]
+    pub fn return_red_cards(&mut self, cards: impl IntoIterator<Item = RedCard>) {
+        cards
+            .into_iter()
+            .for_each(|card| self.red_deck.add_card_bottom(card));
+    }
+
     /// Retrieve card from the green deck
     #[inline]
     pub fn get_green_card(&mut self) -> Option<GreenCard> {
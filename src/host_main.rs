@@ -1,41 +1,195 @@
-use crate::actors::dealer::Dealer;
-use crate::actors::score_handler::{Score, ScoreManager, ScoreManagerMsg};
+use crate::actors::bot_player::{BotArgs, BotPlayer};
+use crate::actors::dealer::{Dealer, DealerMsg};
+use crate::actors::game_coordinator::{CoordinatorArgs, CoordinatorMsg, GameCoordinator};
+use crate::actors::lobby::{Lobby, LobbyMsg};
+use crate::actors::score_handler::ScoreManager;
+use crate::bots::strategy;
 use crate::deck_handler::DeckHandler;
+use crate::rules;
+use crate::tls;
+use crate::transport::{self, Endpoint, Listener, Transport};
 use anyhow::Result;
-use apples_utils::{
-    config::Config, consts::CONFIG_TOML, game_mode::GameMode, setup_tracing::setup_logging,
-};
-use core::num::NonZeroUsize;
-use ractor::{cast, Actor, ActorProcessingErr, ActorRef};
+use apples_protocol::{handshake, Capture, FrameCodec, Hello, Metrics};
+use apples_utils::{config::Config, consts::CONFIG_TOML};
+use ractor::{Actor, ActorRef};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use tokio_rustls::TlsAcceptor;
+use tokio_util::codec::Framed;
+
+/// How long a fresh connection gets to complete the version handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often the host logs the traffic of its connections.
+const STATUS_INTERVAL: Duration = Duration::from_secs(30);
 
 #[doc = include_str!("../doc/host.md")]
-pub async fn host_main(players: usize, bots: usize) -> Result<()> {
+pub async fn host_main(players: usize, bots: usize, capture: Option<PathBuf>) -> Result<()> {
     let config = Config::parse_config(CONFIG_TOML.into())?;
+    let endpoint = match config.unix_socket_path() {
+        Some(path) => Endpoint::Unix(path.to_owned()),
+        None => Endpoint::Tcp(config.socket().into()),
+    };
+    let mut listeners = vec![Listener::bind(&endpoint).await?];
+    tracing::info!("Listening on {endpoint}");
+    if let Some(addr) = config.websocket_addr() {
+        let endpoint = Endpoint::WebSocket(addr.into());
+        listeners.push(Listener::bind(&endpoint).await?);
+        tracing::info!("Listening on {endpoint}");
+    }
+    let capture = capture.map(Capture::create).transpose()?;
+    serve(config, listeners, players, bots, capture).await
+}
+
+/// Runs a whole game, accepting players from `listeners` until every seat is
+/// taken and recording their frames to `capture`, one stream per connection.
+///
+/// TLS, when enabled, is not applied to WebSocket connections: browsers get it
+/// from `wss://` in front of the host instead.
+pub async fn serve(
+    config: Config,
+    mut listeners: Vec<Listener>,
+    players: usize,
+    bots: usize,
+    capture: Option<Capture>,
+) -> Result<()> {
+    let rules = rules::from_mode(config.game_mode());
+
+    let acceptor = if config.tls().enabled() {
+        Some(tls::acceptor(config.tls())?)
+    } else {
+        None
+    };
+    let win_condition = config
+        .get_required_apples(players + bots)
+        .expect("failed to get win condition");
+
+    let deck = {
+        let mut deck = DeckHandler::new();
+        deck.load_decks(
+            config.red_deck_path().into(),
+            config.green_deck_path().into(),
+        )
+        .await?;
+        deck
+    };
+
+    let (dealer, _) = Actor::spawn(None, Dealer, deck).await?;
+    let (score_manager, _) = Actor::spawn(None, ScoreManager, ()).await?;
+    let (coordinator, mut coordinator_handle) = Actor::spawn(
+        None,
+        GameCoordinator,
+        CoordinatorArgs {
+            dealer: dealer.clone(),
+            score_manager: score_manager.clone(),
+            rules,
+            seats: players + bots,
+            win_condition,
+        },
+    )
+    .await?;
+
+    for bot in 0..bots {
+        let args = BotArgs {
+            coordinator: coordinator.clone(),
+            strategy: strategy::from_kind(config.bot_strategy()),
+        };
+        let (player, _) = Actor::spawn(None, BotPlayer, args).await?;
+        let name = format!("Bot {bot}");
+        ractor::call!(
+            coordinator,
+            CoordinatorMsg::Join,
+            name,
+            player.get_derived()
+        )??;
+    }
+
+    let (lobby, _) = Actor::spawn(None, Lobby, (coordinator, config.heartbeat())).await?;
+    tracing::info!("Waiting for {} players", players + bots);
 
-    match config.game_mode() {
-        GameMode::Original => {
-            let _tcp_listener = tokio::net::TcpListener::bind(config.socket()).await?;
-            let _win_condition = config
-                .get_required_apples(players + bots)
-                .expect("failed to get win condition");
-
-            let deck = {
-                let mut deck = DeckHandler::new();
-                deck.load_decks(
-                    config.red_deck_path().into(),
-                    config.green_deck_path().into(),
-                )
-                .await?;
-                deck
-            };
-
-            let (dealer, _) = Actor::spawn(None, Dealer, deck).await?;
-            let (score_manager, _) = Actor::spawn(None, ScoreManager, ()).await?;
-
-            tokio::time::sleep(tokio::time::Duration::from_millis(300)).await;
-            let _ = deck;
+    let mut streams = 0..;
+    let mut status = tokio::time::interval_at(
+        tokio::time::Instant::now() + STATUS_INTERVAL,
+        STATUS_INTERVAL,
+    );
+
+    loop {
+        tokio::select! {
+            finished = &mut coordinator_handle => {
+                finished?;
+                break;
+            }
+            accepted = transport::accept_any(&mut listeners) => {
+                let (index, stream) = accepted?;
+                tracing::info!("Accepted connection from {}", stream.peer());
+                let acceptor = acceptor.clone().filter(|_| !listeners[index].is_websocket());
+                let capture = capture.as_ref().zip(streams.next()).map(|(capture, n)| capture.stream(n));
+                tokio::spawn(negotiate(stream, acceptor, capture, lobby.clone()));
+            }
+            _ = status.tick() => log_status(&lobby, &dealer).await?,
         }
-        _ => todo!("unsupported for now, original is supported"),
     }
+
+    log_status(&lobby, &dealer).await?;
+    ractor::call!(lobby, LobbyMsg::Shutdown)?;
+    lobby.stop(None);
+    dealer.stop(None);
+    score_manager.stop(None);
+    if let Some(capture) = capture {
+        tokio::task::spawn_blocking(move || capture.flush()).await??;
+    }
+    Ok(())
+}
+
+/// Settle on a protocol version with a new peer, after the TLS handshake if
+/// enabled, before handing it to the lobby.
+async fn negotiate(
+    stream: Box<dyn Transport>,
+    acceptor: Option<TlsAcceptor>,
+    capture: Option<Capture>,
+    lobby: ActorRef<LobbyMsg>,
+) {
+    let peer = stream.peer();
+    let handshake = async {
+        let stream = match acceptor {
+            Some(acceptor) => tls::accept(&acceptor, stream).await?,
+            None => stream,
+        };
+        let mut codec = FrameCodec::with_default_limit().with_metrics(Arc::default());
+        codec.set_capture(capture);
+        let mut framed = Framed::new(stream, codec);
+        let session = handshake::respond(&mut framed, Hello::default()).await?;
+        anyhow::Ok((framed, session))
+    };
+    match tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await {
+        Ok(Ok((framed, session))) => {
+            tracing::debug!(
+                "{peer} speaks protocol version {} with {:?} payloads",
+                session.version,
+                session.format
+            );
+            let _ = lobby.cast(LobbyMsg::Accept(framed, session.format));
+        }
+        Ok(Err(e)) => tracing::warn!("Handshake with {peer} failed: {e}"),
+        Err(_) => tracing::warn!("{peer} did not complete the handshake in time"),
+    }
+}
+
+/// Logs deck sizes and the codec counters of every connection, then their totals.
+async fn log_status(lobby: &ActorRef<LobbyMsg>, dealer: &ActorRef<DealerMsg>) -> Result<()> {
+    let (red, green) = ractor::call!(dealer, DealerMsg::GetDeckSizes)?;
+    let connections = ractor::call!(lobby, LobbyMsg::Status)?;
+    tracing::info!(
+        "{} connections, {red} red and {green} green cards left",
+        connections.len()
+    );
+    let mut total = Metrics::default();
+    for connection in connections {
+        let who = connection
+            .player
+            .map_or(String::from("unseated"), |player| player.to_string());
+        tracing::info!("  {who} at {}: {}", connection.peer, connection.metrics);
+        total += connection.metrics;
+    }
+    tracing::info!("  total: {total}");
     Ok(())
 }
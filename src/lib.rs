@@ -1,6 +1,11 @@
 pub mod actors;
+pub mod bots;
+pub mod capture_main;
 pub mod client_main;
 pub mod deck_handler;
 pub mod host_main;
+pub mod rules;
+pub mod tls;
+pub mod transport;
 #[cfg(test)]
 mod tests;
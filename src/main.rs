@@ -1,6 +1,6 @@
 use {
     anyhow::Result,
-    apples2apples::{client_main::client_main, host_main::host_main},
+    apples2apples::{capture_main::capture_main, client_main::client_main, host_main::host_main},
     apples_utils::cli::{parse_args, Mode},
     apples_utils::setup_tracing::setup_logging,
 };
@@ -10,8 +10,13 @@ async fn main() -> Result<()> {
     setup_logging()?;
     let mode = parse_args();
     match mode {
-        Mode::Host { players, bots } => host_main(players, bots).await?,
-        Mode::Client { ip } => client_main(ip.parse()?).await?,
+        Mode::Host {
+            players,
+            bots,
+            capture,
+        } => host_main(players, bots, capture).await?,
+        Mode::Client { ip, name, capture } => client_main(ip.parse()?, name, capture).await?,
+        Mode::Capture(command) => capture_main(command).await?,
     }
     Ok(())
 }
@@ -0,0 +1,12 @@
+use super::GameRules;
+
+/// Red cards are played blind and a random green card is drawn from the deck
+/// once every submission is in; the judge then picks as in `Original`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ApplePotpourri;
+
+impl GameRules for ApplePotpourri {
+    fn blind_submissions(&self) -> bool {
+        true
+    }
+}
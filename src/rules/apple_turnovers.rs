@@ -0,0 +1,33 @@
+use {
+    super::{GameRules, Original},
+    apples_core::PlayerId,
+};
+
+/// Judged like `Original`, but the winner of a round judges the next one.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AppleTurnovers;
+
+impl GameRules for AppleTurnovers {
+    fn next_judge(&self, judge: PlayerId, players: &[PlayerId], winners: &[PlayerId]) -> PlayerId {
+        match winners.first() {
+            Some(&winner) if winner != judge && players.contains(&winner) => winner,
+            _ => Original.next_judge(judge, players, winners),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::AppleTurnovers, crate::rules::GameRules, apples_core::PlayerId};
+
+    #[test]
+    fn winner_judges_next() {
+        let players = [PlayerId(0), PlayerId(1), PlayerId(2)];
+        let rules = AppleTurnovers;
+        assert_eq!(
+            rules.next_judge(PlayerId(0), &players, &[PlayerId(2)]),
+            PlayerId(2)
+        );
+        assert_eq!(rules.next_judge(PlayerId(0), &players, &[]), PlayerId(1));
+    }
+}
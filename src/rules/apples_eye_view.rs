@@ -0,0 +1,75 @@
+use {
+    super::{submission_of, GameRules, Outcome, Submission},
+    apples_core::{
+        cards::card::{Card, CardId},
+        PlayerId,
+    },
+};
+
+/// The judge secretly plays a red card; everyone else tries to spot it and
+/// scores a green card when they do.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ApplesEyeView;
+
+impl GameRules for ApplesEyeView {
+    fn judge_plays(&self) -> bool {
+        true
+    }
+
+    fn pickers(&self, judge: PlayerId, players: &[PlayerId]) -> Vec<PlayerId> {
+        players.iter().copied().filter(|&id| id != judge).collect()
+    }
+
+    fn score(
+        &self,
+        judge: PlayerId,
+        submissions: &[Submission],
+        picks: &[(PlayerId, CardId)],
+    ) -> Option<Outcome> {
+        let (_, cards) = submissions.iter().find(|(owner, _)| *owner == judge)?;
+        let red = cards.first()?.id();
+        let winners = picks
+            .iter()
+            .filter(|&&(_, pick)| {
+                submission_of(submissions, pick).is_some_and(|(owner, _)| *owner == judge)
+            })
+            .map(|&(picker, _)| picker)
+            .collect();
+        Some(Outcome { winners, red })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::ApplesEyeView,
+        crate::rules::GameRules,
+        apples_core::{cards::card::Card, PlayerId, RedCard},
+    };
+
+    #[test]
+    fn players_matching_the_judge_score() {
+        let (judge, alice, bob) = (PlayerId(0), PlayerId(1), PlayerId(2));
+        let submissions = [
+            (
+                judge,
+                vec![RedCard::new(10usize, "Volcanoes", "Hot stuff.")],
+            ),
+            (
+                alice,
+                vec![RedCard::new(11usize, "Ice Cream", "Cold stuff.")],
+            ),
+            (bob, vec![RedCard::new(12usize, "Soup", "Warm stuff.")]),
+        ];
+        let picks = [
+            (alice, submissions[0].1[0].id()),
+            (bob, submissions[1].1[0].id()),
+        ];
+
+        let outcome = ApplesEyeView
+            .score(judge, &submissions, &picks)
+            .expect("judge played a card");
+        assert_eq!(outcome.winners, vec![alice]);
+        assert_eq!(outcome.red, submissions[0].1[0].id());
+    }
+}
@@ -0,0 +1,76 @@
+use {
+    super::{GameRules, Harvest},
+    apples_core::GreenCard,
+    rand::Rng,
+};
+
+/// Judged like `Original`, but some green cards demand two red cards per
+/// submission and a bad harvest makes everyone redraw their hand first.
+#[derive(Debug, Clone, Copy)]
+pub struct BadHarvest {
+    /// Chance a green card asks for two red cards.
+    pub double_chance: f64,
+    /// Chance every hand is discarded and redrawn.
+    pub bad_chance: f64,
+}
+
+impl Default for BadHarvest {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            double_chance: 0.25,
+            bad_chance: 0.1,
+        }
+    }
+}
+
+impl GameRules for BadHarvest {
+    fn harvest(&self, _greens: &[GreenCard]) -> Harvest {
+        let mut rng = rand::thread_rng();
+        let reds = if rng.gen_bool(self.double_chance) {
+            2
+        } else {
+            1
+        };
+        Harvest {
+            reds,
+            redraw: rng.gen_bool(self.bad_chance),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::BadHarvest,
+        crate::rules::{GameRules, Harvest},
+        apples_core::{cards::card::Card, GreenCard, PlayerId, RedCard},
+    };
+
+    #[test]
+    fn harvest_follows_chances() {
+        let green = GreenCard::new(1usize, "Juicy", "Succulent, moist.");
+        let rules = BadHarvest {
+            double_chance: 1.0,
+            bad_chance: 0.0,
+        };
+        assert_eq!(
+            rules.harvest(&[green]),
+            Harvest {
+                reds: 2,
+                redraw: false
+            }
+        );
+
+        let (judge, alice) = (PlayerId(0), PlayerId(1));
+        let cards = vec![
+            RedCard::new(10usize, "Peaches", "Fuzzy fruit."),
+            RedCard::new(11usize, "Mangoes", "Tropical fruit."),
+        ];
+        let picks = [(judge, cards[1].id())];
+        let outcome = rules
+            .score(judge, &[(alice, cards)], &picks)
+            .expect("judge picked a submission");
+        assert_eq!(outcome.winners, vec![alice]);
+    }
+}
@@ -0,0 +1,11 @@
+use super::GameRules;
+
+/// The judge picks the worst match and its owner wins the green card.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CrabApples;
+
+impl GameRules for CrabApples {
+    fn pick_worst(&self) -> bool {
+        true
+    }
+}
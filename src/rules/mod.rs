@@ -0,0 +1,172 @@
+pub mod apple_potpourri;
+pub mod apple_turnovers;
+pub mod apples_eye_view;
+pub mod bad_harvest;
+pub mod crab_apples;
+pub mod original;
+pub mod two_for_one;
+
+use {
+    apples_core::{
+        cards::card::{Card, CardId},
+        GreenCard, PlayerId, RedCard,
+    },
+    apples_utils::game_mode::GameMode,
+};
+
+pub use {
+    apple_potpourri::ApplePotpourri, apple_turnovers::AppleTurnovers,
+    apples_eye_view::ApplesEyeView, bad_harvest::BadHarvest, crab_apples::CrabApples,
+    original::Original, two_for_one::TwoForOne,
+};
+
+/// Amount of red cards every player holds at the start of a round.
+pub const HAND_SIZE: usize = 7;
+
+/// Red cards played by one player in a round.
+pub type Submission = (PlayerId, Vec<RedCard>);
+
+/// How red cards are gathered for a round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Harvest {
+    /// Red cards every submitter has to play.
+    pub reds: usize,
+    /// Whether every hand is discarded and redrawn before submitting.
+    pub redraw: bool,
+}
+
+impl Default for Harvest {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            reds: 1,
+            redraw: false,
+        }
+    }
+}
+
+/// Result of a judged round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// Players receiving a green card.
+    pub winners: Vec<PlayerId>,
+    /// A red card of the submission that decided the round.
+    pub red: CardId,
+}
+
+/// Rule set the `GameCoordinator` consults while running a game.
+pub trait GameRules: Send + Sync + 'static {
+    /// Red cards every hand is refilled to between rounds.
+    fn hand_size(&self) -> usize {
+        HAND_SIZE
+    }
+
+    /// Player judging the round after `judge`, the next seat by default.
+    /// With nobody seated, `judge` keeps the role.
+    fn next_judge(&self, judge: PlayerId, players: &[PlayerId], _winners: &[PlayerId]) -> PlayerId {
+        if players.is_empty() {
+            return judge;
+        }
+        let seat = players.iter().position(|&id| id == judge).unwrap_or(0);
+        players[(seat + 1) % players.len()]
+    }
+
+    /// Whether the judge secretly plays a red card alongside the submissions.
+    fn judge_plays(&self) -> bool {
+        false
+    }
+
+    /// Whether red cards are played before the green cards are drawn.
+    fn blind_submissions(&self) -> bool {
+        false
+    }
+
+    /// Green cards revealed every round; each winner takes all of them.
+    fn greens_per_round(&self) -> usize {
+        1
+    }
+
+    /// Decide how red cards are gathered once the green cards are revealed.
+    fn harvest(&self, _greens: &[GreenCard]) -> Harvest {
+        Harvest::default()
+    }
+
+    /// Whether pickers look for the worst match instead of the best.
+    fn pick_worst(&self) -> bool {
+        false
+    }
+
+    /// Players asked to pick a card among the submissions.
+    fn pickers(&self, judge: PlayerId, _players: &[PlayerId]) -> Vec<PlayerId> {
+        vec![judge]
+    }
+
+    /// Decide who scores once every picker made a choice; by default the
+    /// owner of the card the judge picked.
+    fn score(
+        &self,
+        judge: PlayerId,
+        submissions: &[Submission],
+        picks: &[(PlayerId, CardId)],
+    ) -> Option<Outcome> {
+        let &(_, pick) = picks.iter().find(|(picker, _)| *picker == judge)?;
+        let (owner, _) = submission_of(submissions, pick)?;
+        Some(Outcome {
+            winners: vec![*owner],
+            red: pick,
+        })
+    }
+
+    /// Player who won the game given the current scores, if the game is over.
+    fn winner(&self, scores: &[(PlayerId, usize)], win_condition: usize) -> Option<PlayerId> {
+        scores
+            .iter()
+            .rev()
+            .filter(|&&(_, score)| score >= win_condition)
+            .max_by_key(|&&(_, score)| score)
+            .map(|&(id, _)| id)
+    }
+}
+
+/// Find the submission a picked card belongs to.
+pub fn submission_of(submissions: &[Submission], card: CardId) -> Option<&Submission> {
+    submissions
+        .iter()
+        .find(|(_, cards)| cards.iter().any(|red| red.id() == card))
+}
+
+/// Select the rule set for the configured game mode.
+pub fn from_mode(mode: GameMode) -> Box<dyn GameRules> {
+    match mode {
+        GameMode::Original => Box::new(Original),
+        GameMode::ApplesEyeView => Box::new(ApplesEyeView),
+        GameMode::BadHarvest => Box::new(BadHarvest::default()),
+        GameMode::TwoForOne => Box::new(TwoForOne),
+        GameMode::CrabApples => Box::new(CrabApples),
+        GameMode::AppleTurnovers => Box::new(AppleTurnovers),
+        GameMode::ApplePotpourri => Box::new(ApplePotpourri),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{GameRules, Original},
+        apples_core::PlayerId,
+    };
+
+    #[test]
+    fn defaults_rotate_judge_and_end_at_win_condition() {
+        let players = [PlayerId(0), PlayerId(1), PlayerId(2)];
+        assert_eq!(Original.next_judge(PlayerId(1), &players, &[]), PlayerId(2));
+        assert_eq!(
+            Original.next_judge(PlayerId(2), &players, &[PlayerId(1)]),
+            PlayerId(0)
+        );
+        assert_eq!(Original.next_judge(PlayerId(2), &[], &[]), PlayerId(2));
+
+        let scores = [(PlayerId(0), 3), (PlayerId(1), 5), (PlayerId(2), 5)];
+        assert_eq!(Original.winner(&scores, 6), None);
+        assert_eq!(Original.winner(&scores, 5), Some(PlayerId(1)));
+    }
+}
@@ -0,0 +1,7 @@
+use super::GameRules;
+
+/// The judge picks the best red card and its owner wins the green card.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Original;
+
+impl GameRules for Original {}
@@ -0,0 +1,12 @@
+use super::GameRules;
+
+/// Two green cards are revealed and one red card is judged against both;
+/// the winner takes both green cards.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TwoForOne;
+
+impl GameRules for TwoForOne {
+    fn greens_per_round(&self) -> usize {
+        2
+    }
+}
@@ -0,0 +1,199 @@
+use super::{spawn_game, Game};
+use crate::actors::bot_player::{BotArgs, BotPlayer};
+use crate::actors::game_coordinator::CoordinatorMsg;
+use crate::bots::{strategy, BotStrategy};
+use crate::rules::{AppleTurnovers, ApplesEyeView, CrabApples, GameRules, Original};
+use apples_core::{GreenCard, PlayerId, RedCard};
+use apples_protocol::{ErrorCode, Message};
+use apples_utils::bot_kind::BotKind;
+use dsl_ractor::{actor, actor_handle, actor_pre_start};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+/// Stands in for the coordinator and forwards what players send it.
+#[actor(
+    msg = CoordinatorMsg,
+    state = UnboundedSender<(PlayerId, Message)>,
+    args = UnboundedSender<(PlayerId, Message)>
+)]
+struct Inbox;
+
+impl Inbox {
+    actor_pre_start!(Ok(args));
+
+    actor_handle!({
+        if let CoordinatorMsg::Inbound(id, msg) = msg {
+            let _ = state.send((id, msg));
+        }
+        Ok(())
+    });
+}
+
+#[tokio::test]
+async fn bots_finish_a_game() -> anyhow::Result<()> {
+    bots_play(Box::new(Original)).await
+}
+
+#[tokio::test]
+async fn bots_play_apples_eye_view() -> anyhow::Result<()> {
+    bots_play(Box::new(ApplesEyeView)).await
+}
+
+#[tokio::test]
+async fn bots_judge_crab_apples() -> anyhow::Result<()> {
+    bots_play(Box::new(CrabApples)).await
+}
+
+#[tokio::test]
+async fn bots_hand_over_judging() -> anyhow::Result<()> {
+    bots_play(Box::new(AppleTurnovers)).await
+}
+
+#[tokio::test]
+async fn bots_pass_without_enough_cards() -> anyhow::Result<()> {
+    let (outbox, mut inbox) = unbounded_channel();
+    let (coordinator, _) = ractor::Actor::spawn(None, Inbox, outbox).await?;
+    let args = BotArgs {
+        coordinator,
+        strategy: strategy::from_kind(BotKind::Random),
+    };
+    let (bot, _) = ractor::Actor::spawn(None, BotPlayer, args).await?;
+
+    let id = PlayerId(4);
+    bot.cast(Message::Welcome { player_id: id })?;
+    bot.cast(Message::PlayRequest { count: 1 })?;
+    let (from, msg) = tokio::time::timeout(std::time::Duration::from_secs(1), inbox.recv())
+        .await?
+        .expect("bot answers");
+    assert_eq!(from, id);
+    match msg {
+        Message::Error(error) => assert_eq!(error.code, ErrorCode::OUT_OF_CARDS),
+        other => panic!("expected a pass, got {other:?}"),
+    }
+
+    bot.stop(None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn bots_keep_cards_until_the_host_confirms_them() -> anyhow::Result<()> {
+    let (outbox, mut inbox) = unbounded_channel();
+    let (coordinator, _) = ractor::Actor::spawn(None, Inbox, outbox).await?;
+    let args = BotArgs {
+        coordinator,
+        strategy: strategy::from_kind(BotKind::Random),
+    };
+    let (bot, _) = ractor::Actor::spawn(None, BotPlayer, args).await?;
+    let mut next = async || {
+        tokio::time::timeout(std::time::Duration::from_secs(1), inbox.recv())
+            .await
+            .ok()
+            .flatten()
+            .map(|(_, msg)| msg)
+    };
+
+    let cards: Vec<RedCard> = (0..3usize)
+        .map(|id| RedCard::new(id, "Apples", "Crisp fruit."))
+        .collect();
+    bot.cast(Message::Welcome {
+        player_id: PlayerId(1),
+    })?;
+    bot.cast(Message::DealHand { cards })?;
+    bot.cast(Message::PlayRequest { count: 1 })?;
+    assert!(matches!(next().await, Some(Message::SubmitRed { cards }) if cards.len() == 1));
+
+    // The host refused that, so every card is still there to play.
+    bot.cast(Message::PlayRequest { count: 3 })?;
+    let Some(Message::SubmitRed { cards }) = next().await else {
+        panic!("bot plays its whole hand");
+    };
+    assert_eq!(cards.len(), 3);
+
+    bot.cast(Message::Submitted { cards })?;
+    bot.cast(Message::PlayRequest { count: 1 })?;
+    match next().await {
+        Some(Message::Error(error)) => assert_eq!(error.code, ErrorCode::OUT_OF_CARDS),
+        other => panic!("expected a pass, got {other:?}"),
+    }
+
+    bot.stop(None);
+    Ok(())
+}
+
+/// Plays the first card and reports the greens it was asked to match.
+struct Recorder(UnboundedSender<Vec<GreenCard>>);
+
+impl BotStrategy for Recorder {
+    fn choose_red(&mut self, greens: &[GreenCard], _hand: &[RedCard]) -> usize {
+        let _ = self.0.send(greens.to_vec());
+        0
+    }
+
+    fn judge(&mut self, _greens: &[GreenCard], _submissions: &[RedCard]) -> usize {
+        0
+    }
+
+    fn judge_worst(&mut self, _greens: &[GreenCard], _submissions: &[RedCard]) -> usize {
+        0
+    }
+}
+
+#[tokio::test]
+async fn bots_forget_the_greens_of_a_voided_round() -> anyhow::Result<()> {
+    let (outbox, _inbox) = unbounded_channel();
+    let (coordinator, _) = ractor::Actor::spawn(None, Inbox, outbox).await?;
+    let (asked, mut matched) = unbounded_channel();
+    let args = BotArgs {
+        coordinator,
+        strategy: Box::new(Recorder(asked)),
+    };
+    let (bot, _) = ractor::Actor::spawn(None, BotPlayer, args).await?;
+
+    let green = GreenCard::new(1usize, "Juicy", "Succulent, moist.");
+    let cards: Vec<RedCard> = (0..2usize)
+        .map(|id| RedCard::new(id, "Apples", "Crisp fruit."))
+        .collect();
+    bot.cast(Message::Welcome {
+        player_id: PlayerId(1),
+    })?;
+    bot.cast(Message::DealHand { cards })?;
+    bot.cast(Message::RevealGreen {
+        judge: PlayerId(0),
+        card: green.clone(),
+    })?;
+    bot.cast(Message::PlayRequest { count: 1 })?;
+    assert_eq!(matched.recv().await, Some(vec![green.clone()]));
+
+    // The round is voided, and the next one is played blind.
+    bot.cast(Message::RoundResult {
+        winners: Vec::new(),
+        greens: vec![green],
+        reds: Vec::new(),
+    })?;
+    bot.cast(Message::PlayRequest { count: 1 })?;
+    assert_eq!(matched.recv().await, Some(Vec::new()));
+
+    bot.stop(None);
+    Ok(())
+}
+
+async fn bots_play(rules: Box<dyn GameRules>) -> anyhow::Result<()> {
+    let kinds = [BotKind::Random, BotKind::Keyword, BotKind::JudgeModel];
+    let Game {
+        coordinator,
+        handle: coordinator_handle,
+        ..
+    } = spawn_game(rules, kinds.len(), 3).await?;
+
+    for kind in kinds {
+        let args = BotArgs {
+            coordinator: coordinator.clone(),
+            strategy: strategy::from_kind(kind),
+        };
+        let (bot, _) = ractor::Actor::spawn(None, BotPlayer, args).await?;
+        let name = format!("{kind:?}");
+        ractor::call!(coordinator, CoordinatorMsg::Join, name, bot.get_derived())??;
+    }
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), coordinator_handle).await??;
+    Ok(())
+}
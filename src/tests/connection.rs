@@ -0,0 +1,139 @@
+use crate::actors::connection::{Connection, ConnectionArgs, ConnectionEvent, ConnectionMsg};
+use crate::transport::Listener;
+use apples_core::PlayerId;
+use apples_protocol::{FrameCodec, Message, PayloadFormat};
+use apples_utils::config::Heartbeat;
+use dsl_ractor::{actor, actor_handle, actor_pre_start};
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_util::codec::Framed;
+
+/// Forwards every connection event to the test body.
+#[actor(msg = ConnectionEvent, state = UnboundedSender<ConnectionEvent>, args = UnboundedSender<ConnectionEvent>)]
+struct Parent;
+
+impl Parent {
+    actor_pre_start!(Ok(args));
+
+    actor_handle!({
+        let _ = state.send(msg);
+        Ok(())
+    });
+}
+
+#[tokio::test]
+async fn relays_frames_and_reports_disconnect() -> anyhow::Result<()> {
+    let (mut listener, connector) = Listener::memory();
+    let client = connector.connect()?;
+    let server = listener.accept().await?;
+
+    let (events, mut received) = unbounded_channel();
+    let (parent, _) = ractor::Actor::spawn(None, Parent, events).await?;
+    let args = ConnectionArgs {
+        stream: Framed::new(server, FrameCodec::with_default_limit()),
+        parent: parent.get_derived(),
+        heartbeat: Heartbeat::default(),
+        format: PayloadFormat::Json,
+    };
+    let (connection, _) = ractor::Actor::spawn(None, Connection, args).await?;
+
+    let mut peer = Framed::new(client, FrameCodec::with_default_limit());
+    let welcome = Message::Welcome {
+        player_id: PlayerId(4),
+    };
+    ractor::cast!(connection, ConnectionMsg::Deliver(welcome.clone()))?;
+    let frame = peer.next().await.expect("frame")?;
+    assert_eq!(Message::decode(&frame)?, welcome);
+
+    let join = Message::JoinRequest {
+        name: String::from("peer"),
+    };
+    peer.send(join.encode()?).await?;
+    match received.recv().await {
+        Some(ConnectionEvent::Frame(id, frame)) => {
+            assert_eq!(id, connection.get_id());
+            assert_eq!(Message::decode(&frame)?, join);
+        }
+        other => panic!("Expected a frame, got {other:?}"),
+    }
+
+    drop(peer);
+    assert!(
+        matches!(
+            received.recv().await,
+            Some(ConnectionEvent::Disconnected(id, None)) if id == connection.get_id()
+        ),
+        "Closing the peer should report a clean disconnect"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn answers_pings_and_drops_silent_peers() -> anyhow::Result<()> {
+    let (mut listener, connector) = Listener::memory();
+    let client = connector.connect()?;
+    let server = listener.accept().await?;
+
+    let (events, mut received) = unbounded_channel();
+    let (parent, _) = ractor::Actor::spawn(None, Parent, events).await?;
+    let args = ConnectionArgs {
+        stream: Framed::new(server, FrameCodec::with_default_limit()),
+        parent: parent.get_derived(),
+        heartbeat: Heartbeat::default(),
+        format: PayloadFormat::Json,
+    };
+    let (connection, _) = ractor::Actor::spawn(None, Connection, args).await?;
+
+    let mut peer = Framed::new(client, FrameCodec::with_default_limit());
+    peer.send(Message::Ping.encode()?).await?;
+    loop {
+        let frame = peer.next().await.expect("frame")?;
+        match Message::decode(&frame)? {
+            Message::Pong => break,
+            Message::Ping => continue,
+            other => panic!("Expected a pong, got {other:?}"),
+        }
+    }
+
+    assert!(
+        matches!(
+            received.recv().await,
+            Some(ConnectionEvent::Disconnected(id, Some(_))) if id == connection.get_id()
+        ),
+        "A peer that stays silent past the idle timeout should be dropped"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn reports_disconnect_when_closed_locally() -> anyhow::Result<()> {
+    let (mut listener, connector) = Listener::memory();
+    let client = connector.connect()?;
+    let server = listener.accept().await?;
+
+    let (events, mut received) = unbounded_channel();
+    let (parent, _) = ractor::Actor::spawn(None, Parent, events).await?;
+    let args = ConnectionArgs {
+        stream: Framed::new(server, FrameCodec::with_default_limit()),
+        parent: parent.get_derived(),
+        heartbeat: Heartbeat::default(),
+        format: PayloadFormat::Json,
+    };
+    let (connection, _) = ractor::Actor::spawn(None, Connection, args).await?;
+
+    ractor::cast!(connection, ConnectionMsg::Close)?;
+    assert!(
+        matches!(
+            received.recv().await,
+            Some(ConnectionEvent::Disconnected(id, None)) if id == connection.get_id()
+        ),
+        "Closing the connection should still report the disconnect"
+    );
+    let mut peer = Framed::new(client, FrameCodec::with_default_limit());
+    assert!(peer.next().await.is_none());
+    assert!(received.try_recv().is_err(), "Disconnect is reported once");
+
+    Ok(())
+}
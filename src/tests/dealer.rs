@@ -1,20 +1,11 @@
+use super::load_decks;
 use crate::actors::dealer::{Dealer, DealerMsg};
 use crate::deck_handler::DeckHandler;
-use apples_utils::{config::Config, consts::CONFIG_TOML};
 use core::num::NonZeroUsize;
 
 #[tokio::test]
 async fn deal_cards_success() -> anyhow::Result<()> {
-    let mut handler = DeckHandler::new();
-    let config = Config::parse_config(CONFIG_TOML.into())?;
-
-    handler
-        .load_decks(
-            config.red_deck_path().into(),
-            config.green_deck_path().into(),
-        )
-        .await?;
-
+    let mut handler = load_decks().await?;
     handler.shuffle();
 
     let (dealer, _) = ractor::Actor::spawn(None, Dealer, handler).await?;
@@ -46,6 +37,32 @@ async fn deal_cards_success() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn redraw_returns_discards_under_the_deck() -> anyhow::Result<()> {
+    let (dealer, _) = ractor::Actor::spawn(None, Dealer, load_decks().await?).await?;
+
+    let (red_size, _) = ractor::call!(dealer, DealerMsg::GetDeckSizes)?;
+    let amount = NonZeroUsize::new(3).expect("failed to create amount");
+    let hand = ractor::call!(dealer, DealerMsg::DealRedCards, amount)??;
+    let fresh = ractor::call!(dealer, DealerMsg::Redraw, hand.clone())??;
+
+    assert_eq!(fresh.len(), hand.len());
+    assert!(
+        fresh.iter().all(|card| !hand.contains(card)),
+        "Discarded cards should not be drawn right back"
+    );
+    let (after, _) = ractor::call!(dealer, DealerMsg::GetDeckSizes)?;
+    assert_eq!(after, red_size - amount.get());
+
+    let empty = ractor::call!(dealer, DealerMsg::Redraw, Vec::new())?;
+    assert!(matches!(
+        empty,
+        Err(crate::actors::dealer::DealError::InvalidAmount { amount: 0 })
+    ));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn deal_cards_insufficient() -> anyhow::Result<()> {
     let handler = DeckHandler::new();
@@ -0,0 +1,578 @@
+use super::{spawn_game, Game};
+use crate::actors::dealer::DealerMsg;
+use crate::actors::game_coordinator::CoordinatorMsg;
+use crate::actors::score_handler::{Score, ScoreManagerMsg};
+use crate::rules::{ApplePotpourri, ApplesEyeView, BadHarvest, GameRules, Original, TwoForOne};
+use apples_core::{cards::card::Card, PlayerId, RedCard};
+use apples_protocol::{ErrorCode, ErrorFrame, Message};
+use dsl_ractor::{actor, actor_handle, actor_pre_start};
+use ractor::ActorRef;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+/// Plays the last cards in hand and always picks the first submission.
+#[actor(msg = Message, state = ScriptedState, args = ScriptedArgs)]
+struct ScriptedPlayer;
+
+struct ScriptedArgs {
+    coordinator: ActorRef<CoordinatorMsg>,
+    results: UnboundedSender<(PlayerId, usize)>,
+}
+
+struct ScriptedState {
+    args: ScriptedArgs,
+    id: Option<PlayerId>,
+    hand: Vec<RedCard>,
+    rounds: usize,
+}
+
+impl ScriptedPlayer {
+    actor_pre_start!(Ok(ScriptedState {
+        args,
+        id: None,
+        hand: Vec::new(),
+        rounds: 0,
+    }));
+
+    actor_handle!({
+        match msg {
+            Message::Welcome { player_id } => state.id = Some(player_id),
+            Message::DealHand { cards } => state.hand.extend(cards),
+            Message::ReplaceHand { cards } => state.hand = cards,
+            Message::PlayRequest { count } => {
+                let id = state.id.expect("welcomed before the first round");
+                let at = state
+                    .hand
+                    .len()
+                    .checked_sub(count)
+                    .expect("hand is refilled every round");
+                let cards = state.hand.split_off(at);
+                let submit = Message::SubmitRed { cards };
+                ractor::cast!(state.args.coordinator, CoordinatorMsg::Inbound(id, submit))?;
+            }
+            Message::JudgeRequest { submissions, .. } => {
+                let id = state.id.expect("welcomed before the first round");
+                let choice = Message::JudgeChoice {
+                    card: submissions[0][0].id(),
+                };
+                ractor::cast!(state.args.coordinator, CoordinatorMsg::Inbound(id, choice))?;
+            }
+            Message::RoundResult { .. } => state.rounds += 1,
+            Message::GameOver { winner } => {
+                let _ = state.args.results.send((winner, state.rounds));
+            }
+            _ => {}
+        }
+        Ok(())
+    });
+}
+
+/// Forwards everything it is sent to a channel.
+#[actor(msg = Message, state = UnboundedSender<Message>, args = UnboundedSender<Message>)]
+struct Observer;
+
+impl Observer {
+    actor_pre_start!(Ok(args));
+
+    actor_handle!({
+        let _ = state.send(msg);
+        Ok(())
+    });
+}
+
+#[tokio::test]
+async fn rejects_illegal_moves_with_an_error_frame() -> anyhow::Result<()> {
+    let Game { coordinator, .. } = spawn_game(Box::new(Original), 2, 1).await?;
+
+    let (outbox, mut inbox) = unbounded_channel();
+    let (observer, _) = ractor::Actor::spawn(None, Observer, outbox).await?;
+    let id = ractor::call!(
+        coordinator,
+        CoordinatorMsg::Join,
+        "early".into(),
+        observer.get_derived()
+    )??;
+
+    let submit = Message::SubmitRed { cards: Vec::new() };
+    ractor::cast!(coordinator, CoordinatorMsg::Inbound(id, submit))?;
+    loop {
+        match inbox.recv().await.expect("coordinator answers") {
+            Message::Error(error) => {
+                assert_eq!(error.code, ErrorCode::ILLEGAL_MOVE);
+                break;
+            }
+            _ => continue,
+        }
+    }
+
+    coordinator.stop(None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn skips_players_who_leave() -> anyhow::Result<()> {
+    let Game {
+        coordinator,
+        handle: coordinator_handle,
+        ..
+    } = spawn_game(Box::new(Original), 3, 2).await?;
+
+    let (results, mut winners) = unbounded_channel();
+    for seat in 0..2 {
+        let args = ScriptedArgs {
+            coordinator: coordinator.clone(),
+            results: results.clone(),
+        };
+        let (player, _) = ractor::Actor::spawn(None, ScriptedPlayer, args).await?;
+        ractor::call!(
+            coordinator,
+            CoordinatorMsg::Join,
+            format!("p{seat}"),
+            player.get_derived()
+        )??;
+    }
+    let (outbox, mut inbox) = unbounded_channel();
+    let (quitter, _) = ractor::Actor::spawn(None, Observer, outbox).await?;
+    let id = ractor::call!(
+        coordinator,
+        CoordinatorMsg::Join,
+        "quitter".into(),
+        quitter.get_derived()
+    )??;
+
+    while !matches!(
+        inbox.recv().await.expect("coordinator deals"),
+        Message::PlayRequest { .. } | Message::JudgeRequest { .. }
+    ) {}
+    ractor::cast!(coordinator, CoordinatorMsg::Leave(id))?;
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), coordinator_handle).await??;
+    let (winner, _) = winners.recv().await.expect("the game ends");
+    assert_ne!(winner, id, "A player who left cannot win");
+    Ok(())
+}
+
+#[tokio::test]
+async fn voids_the_round_when_the_judge_leaves() -> anyhow::Result<()> {
+    let Game {
+        coordinator,
+        handle: coordinator_handle,
+        ..
+    } = spawn_game(Box::new(ApplesEyeView), 3, 1).await?;
+
+    let (outbox, mut inbox) = unbounded_channel();
+    let (judge, _) = ractor::Actor::spawn(None, Observer, outbox).await?;
+    let judge = ractor::call!(
+        coordinator,
+        CoordinatorMsg::Join,
+        "judge".into(),
+        judge.get_derived()
+    )??;
+    let (results, mut winners) = unbounded_channel();
+    for seat in 1..3 {
+        let args = ScriptedArgs {
+            coordinator: coordinator.clone(),
+            results: results.clone(),
+        };
+        let (player, _) = ractor::Actor::spawn(None, ScriptedPlayer, args).await?;
+        ractor::call!(
+            coordinator,
+            CoordinatorMsg::Join,
+            format!("p{seat}"),
+            player.get_derived()
+        )??;
+    }
+
+    // The judge leaves before secretly playing their red card.
+    while !matches!(
+        inbox.recv().await.expect("coordinator deals"),
+        Message::PlayRequest { .. }
+    ) {}
+    ractor::cast!(coordinator, CoordinatorMsg::Leave(judge))?;
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), coordinator_handle).await??;
+    let (winner, _) = tokio::time::timeout(std::time::Duration::from_secs(1), winners.recv())
+        .await?
+        .expect("the game goes on to a winner");
+    assert_ne!(winner, judge);
+    Ok(())
+}
+
+#[tokio::test]
+async fn ends_when_players_leave_between_rounds() -> anyhow::Result<()> {
+    let Game {
+        coordinator,
+        handle: coordinator_handle,
+        ..
+    } = spawn_game(Box::new(Original), 2, 5).await?;
+
+    let mut seats = Vec::new();
+    for name in ["judge", "quitter"] {
+        let (outbox, inbox) = unbounded_channel();
+        let (observer, _) = ractor::Actor::spawn(None, Observer, outbox).await?;
+        let id = ractor::call!(
+            coordinator,
+            CoordinatorMsg::Join,
+            name.into(),
+            observer.get_derived()
+        )??;
+        seats.push((id, inbox));
+    }
+    let [(judge, mut judge_inbox), (id, mut inbox)] = <[_; 2]>::try_from(seats).expect("two seats");
+
+    let mut hand = Vec::new();
+    loop {
+        match inbox.recv().await.expect("coordinator deals") {
+            Message::DealHand { cards } => hand.extend(cards),
+            Message::PlayRequest { count } => {
+                let submit = Message::SubmitRed {
+                    cards: hand.split_off(hand.len() - count),
+                };
+                ractor::cast!(coordinator, CoordinatorMsg::Inbound(id, submit))?;
+                break;
+            }
+            _ => {}
+        }
+    }
+    loop {
+        if let Message::JudgeRequest { submissions, .. } =
+            judge_inbox.recv().await.expect("judge is asked to pick")
+        {
+            let choice = Message::JudgeChoice {
+                card: submissions[0][0].id(),
+            };
+            // Handled before the `NextRound` the pick schedules.
+            ractor::cast!(coordinator, CoordinatorMsg::Inbound(judge, choice))?;
+            ractor::cast!(coordinator, CoordinatorMsg::Leave(id))?;
+            break;
+        }
+    }
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), coordinator_handle).await??;
+    loop {
+        if let Message::GameOver { winner } = judge_inbox.recv().await.expect("the game ends") {
+            assert_eq!(winner, judge, "A player who left cannot win");
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn confirms_submissions_and_skips_players_who_pass() -> anyhow::Result<()> {
+    let Game {
+        coordinator,
+        handle: coordinator_handle,
+        ..
+    } = spawn_game(Box::new(Original), 3, 1).await?;
+
+    let (results, mut winners) = unbounded_channel();
+    let args = ScriptedArgs {
+        coordinator: coordinator.clone(),
+        results,
+    };
+    let (judge, _) = ractor::Actor::spawn(None, ScriptedPlayer, args).await?;
+    ractor::call!(
+        coordinator,
+        CoordinatorMsg::Join,
+        "judge".into(),
+        judge.get_derived()
+    )??;
+    let mut seats = Vec::new();
+    for name in ["player", "passer"] {
+        let (outbox, inbox) = unbounded_channel();
+        let (observer, _) = ractor::Actor::spawn(None, Observer, outbox).await?;
+        let id = ractor::call!(
+            coordinator,
+            CoordinatorMsg::Join,
+            name.into(),
+            observer.get_derived()
+        )??;
+        seats.push((id, inbox));
+    }
+    let [(player, mut inbox), (passer, mut passer_inbox)] =
+        <[_; 2]>::try_from(seats).expect("two seats");
+
+    while !matches!(
+        passer_inbox.recv().await.expect("coordinator deals"),
+        Message::PlayRequest { .. }
+    ) {}
+    let pass = Message::Error(ErrorFrame::new(ErrorCode::OUT_OF_CARDS, "No cards"));
+    ractor::cast!(coordinator, CoordinatorMsg::Inbound(passer, pass))?;
+
+    let mut hand = Vec::new();
+    let played = loop {
+        match inbox.recv().await.expect("coordinator deals") {
+            Message::DealHand { cards } => hand.extend(cards),
+            Message::PlayRequest { count } => {
+                let cards = hand.split_off(hand.len() - count);
+                let submit = Message::SubmitRed {
+                    cards: cards.clone(),
+                };
+                ractor::cast!(coordinator, CoordinatorMsg::Inbound(player, submit))?;
+                break cards;
+            }
+            _ => {}
+        }
+    };
+    loop {
+        if let Message::Submitted { cards } = inbox.recv().await.expect("submission confirmed") {
+            assert_eq!(cards, played);
+            break;
+        }
+    }
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), coordinator_handle).await??;
+    let (winner, _) = winners.recv().await.expect("the game ends");
+    assert_eq!(winner, player, "The only submission wins");
+    Ok(())
+}
+
+#[tokio::test]
+async fn announces_voided_rounds() -> anyhow::Result<()> {
+    let Game { coordinator, .. } = spawn_game(Box::new(Original), 3, 1).await?;
+
+    let mut seats = Vec::new();
+    for name in ["judge", "p1", "p2"] {
+        let (outbox, inbox) = unbounded_channel();
+        let (observer, _) = ractor::Actor::spawn(None, Observer, outbox).await?;
+        let id = ractor::call!(
+            coordinator,
+            CoordinatorMsg::Join,
+            name.into(),
+            observer.get_derived()
+        )??;
+        seats.push((id, inbox));
+    }
+
+    for (id, inbox) in &mut seats[1..] {
+        while !matches!(
+            inbox.recv().await.expect("coordinator deals"),
+            Message::PlayRequest { .. }
+        ) {}
+        let pass = Message::Error(ErrorFrame::new(ErrorCode::OUT_OF_CARDS, "No cards"));
+        ractor::cast!(coordinator, CoordinatorMsg::Inbound(*id, pass))?;
+    }
+    let (_, inbox) = &mut seats[0];
+    loop {
+        if let Message::RoundResult { winners, reds, .. } =
+            inbox.recv().await.expect("everyone hears of the void")
+        {
+            assert!(winners.is_empty() && reds.is_empty());
+            break;
+        }
+    }
+
+    coordinator.stop(None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn frees_seats_left_in_the_lobby() -> anyhow::Result<()> {
+    let Game { coordinator, .. } = spawn_game(Box::new(Original), 2, 1).await?;
+
+    let mut seats = Vec::new();
+    for name in ["quitter", "judge", "player"] {
+        let (outbox, inbox) = unbounded_channel();
+        let (observer, _) = ractor::Actor::spawn(None, Observer, outbox).await?;
+        let id = ractor::call!(
+            coordinator,
+            CoordinatorMsg::Join,
+            name.into(),
+            observer.get_derived()
+        )??;
+        if name == "quitter" {
+            ractor::cast!(coordinator, CoordinatorMsg::Leave(id))?;
+        }
+        seats.push((id, inbox));
+    }
+    let [(quitter, _), (judge, _), (_, mut inbox)] =
+        <[_; 3]>::try_from(seats).expect("three joins");
+    assert_eq!(judge, quitter, "The next player takes the empty seat");
+
+    loop {
+        match inbox.recv().await.expect("the game starts") {
+            Message::PlayRequest { .. } => break,
+            Message::GameOver { .. } => panic!("the game ended short-handed"),
+            _ => {}
+        }
+    }
+
+    coordinator.stop(None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn plays_until_win_condition() -> anyhow::Result<()> {
+    play_until_win_condition(Box::new(Original)).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn bad_harvest_submits_pairs_and_redraws() -> anyhow::Result<()> {
+    let rules = BadHarvest {
+        double_chance: 1.0,
+        bad_chance: 1.0,
+    };
+    let hand_size = rules.hand_size();
+    let Game {
+        dealer,
+        coordinator,
+        handle: coordinator_handle,
+        ..
+    } = spawn_game(Box::new(rules), 3, 1).await?;
+    let (reds, _) = ractor::call!(dealer, DealerMsg::GetDeckSizes)?;
+
+    let mut seats = Vec::new();
+    for name in ["judge", "p1", "p2"] {
+        let (outbox, inbox) = unbounded_channel();
+        let (observer, _) = ractor::Actor::spawn(None, Observer, outbox).await?;
+        let id = ractor::call!(
+            coordinator,
+            CoordinatorMsg::Join,
+            name.into(),
+            observer.get_derived()
+        )??;
+        seats.push((id, inbox));
+    }
+
+    let mut hands = Vec::new();
+    for (_, inbox) in &mut seats[1..] {
+        let mut dealt = Vec::new();
+        let hand = loop {
+            match inbox.recv().await.expect("coordinator deals") {
+                Message::DealHand { cards } => dealt.extend(cards),
+                Message::ReplaceHand { cards } => break cards,
+                _ => {}
+            }
+        };
+        assert!(
+            hand.iter().all(|card| !dealt.contains(card)),
+            "A bad harvest replaces the whole hand"
+        );
+        while !matches!(
+            inbox.recv().await.expect("coordinator asks for cards"),
+            Message::PlayRequest { count: 2 }
+        ) {}
+        hands.push(hand);
+    }
+    let (left, _) = ractor::call!(dealer, DealerMsg::GetDeckSizes)?;
+    assert_eq!(
+        left,
+        reds - 3 * hand_size,
+        "Discarded hands go back into the deck"
+    );
+
+    for ((id, inbox), hand) in seats[1..].iter_mut().zip(&hands) {
+        let attempts = [
+            vec![hand[0].clone()],
+            vec![hand[0].clone(), hand[0].clone()],
+        ];
+        for cards in attempts {
+            let submit = Message::SubmitRed { cards };
+            ractor::cast!(coordinator, CoordinatorMsg::Inbound(*id, submit))?;
+            loop {
+                match inbox.recv().await.expect("coordinator answers") {
+                    Message::Error(error) => {
+                        assert_eq!(error.code, ErrorCode::ILLEGAL_MOVE);
+                        break;
+                    }
+                    Message::Submitted { .. } => panic!("accepted an invalid pair"),
+                    _ => {}
+                }
+            }
+        }
+        let pair = hand[..2].to_vec();
+        let submit = Message::SubmitRed {
+            cards: pair.clone(),
+        };
+        ractor::cast!(coordinator, CoordinatorMsg::Inbound(*id, submit))?;
+        loop {
+            if let Message::Submitted { cards } = inbox.recv().await.expect("pair accepted") {
+                assert_eq!(cards, pair);
+                break;
+            }
+        }
+    }
+
+    let (judge, inbox) = &mut seats[0];
+    let choice = loop {
+        if let Message::JudgeRequest { submissions, .. } =
+            inbox.recv().await.expect("judge is asked to pick")
+        {
+            assert_eq!(submissions.len(), 2);
+            assert!(submissions.iter().all(|cards| cards.len() == 2));
+            break submissions[0][1].id();
+        }
+    };
+    ractor::cast!(
+        coordinator,
+        CoordinatorMsg::Inbound(*judge, Message::JudgeChoice { card: choice })
+    )?;
+    tokio::time::timeout(std::time::Duration::from_secs(5), coordinator_handle).await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn bad_harvest_plays_until_win_condition() -> anyhow::Result<()> {
+    let rules = BadHarvest {
+        double_chance: 1.0,
+        bad_chance: 1.0,
+    };
+    play_until_win_condition(Box::new(rules)).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn two_for_one_awards_both_greens() -> anyhow::Result<()> {
+    let rounds = play_until_win_condition(Box::new(TwoForOne)).await?;
+    assert_eq!(rounds, 1, "The first winner should take both green cards");
+    Ok(())
+}
+
+#[tokio::test]
+async fn apple_potpourri_plays_blind() -> anyhow::Result<()> {
+    play_until_win_condition(Box::new(ApplePotpourri)).await?;
+    Ok(())
+}
+
+/// Plays a scripted game and returns how many rounds it took.
+async fn play_until_win_condition(rules: Box<dyn GameRules>) -> anyhow::Result<usize> {
+    let (seats, win_condition) = (3, 2);
+    let Game {
+        score_manager,
+        coordinator,
+        handle: coordinator_handle,
+        ..
+    } = spawn_game(rules, seats, win_condition).await?;
+
+    let (results, mut winners) = unbounded_channel();
+    for seat in 0..seats {
+        let args = ScriptedArgs {
+            coordinator: coordinator.clone(),
+            results: results.clone(),
+        };
+        let (player, _) = ractor::Actor::spawn(None, ScriptedPlayer, args).await?;
+        let id = ractor::call!(
+            coordinator,
+            CoordinatorMsg::Join,
+            format!("p{seat}"),
+            player.get_derived()
+        )??;
+        assert_eq!(id, PlayerId(seat));
+    }
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), coordinator_handle).await??;
+
+    let (winner, rounds) = winners
+        .recv()
+        .await
+        .expect("every player sees the game end");
+    let score = ractor::call!(score_manager, ScoreManagerMsg::RetrieveScore, winner)??;
+    assert_eq!(
+        score,
+        Score(win_condition),
+        "Winner should stop at the win condition"
+    );
+
+    Ok(rounds)
+}
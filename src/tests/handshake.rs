@@ -0,0 +1,81 @@
+use apples_protocol::{
+    handshake, Features, Flags, Frame, FrameCodec, Hello, Kind, PayloadFormat, ProtoError,
+};
+use bytes::Bytes;
+use futures_util::{SinkExt, StreamExt};
+use tokio_util::codec::Framed;
+
+fn hello(min_version: u16, max_version: u16) -> Hello {
+    Hello {
+        min_version,
+        max_version,
+        features: Features::empty(),
+        formats: vec![PayloadFormat::Json],
+    }
+}
+
+#[tokio::test]
+async fn settles_on_highest_common_version() -> anyhow::Result<()> {
+    let (client, host) = tokio::io::duplex(1024);
+    let mut client = Framed::new(client, FrameCodec::default());
+    let mut host = Framed::new(host, FrameCodec::default());
+
+    let (client_session, host_session) = tokio::join!(
+        handshake::initiate(&mut client, hello(1, 3)),
+        handshake::respond(&mut host, hello(1, 2)),
+    );
+    assert_eq!(client_session?.version, 2);
+    assert_eq!(host_session?.version, 2);
+    assert_eq!(client.codec().version(), 2);
+    assert!(host.codec().is_negotiated());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rejects_disjoint_ranges() {
+    let (client, host) = tokio::io::duplex(1024);
+    let mut client = Framed::new(client, FrameCodec::default());
+    let mut host = Framed::new(host, FrameCodec::default());
+
+    let (client_session, host_session) = tokio::join!(
+        handshake::initiate(&mut client, hello(3, 4)),
+        handshake::respond(&mut host, hello(1, 2)),
+    );
+    assert!(matches!(
+        client_session,
+        Err(ProtoError::NoCommonVersion { remote_max: 2, .. })
+    ));
+    assert!(matches!(
+        host_session,
+        Err(ProtoError::NoCommonVersion { remote_min: 3, .. })
+    ));
+}
+
+#[tokio::test]
+async fn refuses_sessions_it_did_not_offer() -> anyhow::Result<()> {
+    let accepts = [
+        r#"{"type":"accept","data":{"version":3,"features":0,"format":"json"}}"#,
+        r#"{"type":"accept","data":{"version":1,"features":1,"format":"json"}}"#,
+        r#"{"type":"accept","data":{"version":1,"features":0,"format":"bincode"}}"#,
+    ];
+    for accept in accepts {
+        let (client, host) = tokio::io::duplex(1024);
+        let mut client = Framed::new(client, FrameCodec::default());
+        let mut host = Framed::new(host, FrameCodec::default());
+
+        let host = async {
+            host.next().await.transpose()?;
+            let frame = Frame::new(Kind::Control, Flags::empty(), Bytes::from(accept));
+            host.send(frame).await
+        };
+        let (client_session, host) =
+            tokio::join!(handshake::initiate(&mut client, hello(1, 2)), host);
+        host?;
+        assert!(
+            matches!(client_session, Err(ProtoError::UnofferedSession)),
+            "{accept} gave {client_session:?}"
+        );
+    }
+    Ok(())
+}
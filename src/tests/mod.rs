@@ -1,2 +1,68 @@
+use crate::actors::dealer::{Dealer, DealerMsg};
+use crate::actors::game_coordinator::{CoordinatorArgs, CoordinatorMsg, GameCoordinator};
+use crate::actors::score_handler::{ScoreManager, ScoreManagerMsg};
+use crate::deck_handler::DeckHandler;
+use crate::rules::GameRules;
+use apples_utils::{config::Config, consts::CONFIG_TOML};
+use ractor::ActorRef;
+use tokio::task::JoinHandle;
+
+mod bot_player;
+mod connection;
 mod dealer;
+mod game_coordinator;
+mod handshake;
 mod score_manager;
+mod tls;
+mod transport;
+
+/// Loads the decks named in `Config.toml`, unshuffled.
+async fn load_decks() -> anyhow::Result<DeckHandler> {
+    let config = Config::parse_config(CONFIG_TOML.into())?;
+    let mut handler = DeckHandler::new();
+    handler
+        .load_decks(
+            config.red_deck_path().into(),
+            config.green_deck_path().into(),
+        )
+        .await?;
+    Ok(handler)
+}
+
+/// The actors behind one game, before anyone joined.
+struct Game {
+    dealer: ActorRef<DealerMsg>,
+    score_manager: ActorRef<ScoreManagerMsg>,
+    coordinator: ActorRef<CoordinatorMsg>,
+    /// Finishes once the game is over.
+    handle: JoinHandle<()>,
+}
+
+/// Spawns a dealer over the configured decks, a score manager and a
+/// coordinator waiting for `seats` players.
+async fn spawn_game(
+    rules: Box<dyn GameRules>,
+    seats: usize,
+    win_condition: usize,
+) -> anyhow::Result<Game> {
+    let (dealer, _) = ractor::Actor::spawn(None, Dealer, load_decks().await?).await?;
+    let (score_manager, _) = ractor::Actor::spawn(None, ScoreManager, ()).await?;
+    let (coordinator, handle) = ractor::Actor::spawn(
+        None,
+        GameCoordinator,
+        CoordinatorArgs {
+            dealer: dealer.clone(),
+            score_manager: score_manager.clone(),
+            rules,
+            seats,
+            win_condition,
+        },
+    )
+    .await?;
+    Ok(Game {
+        dealer,
+        score_manager,
+        coordinator,
+        handle,
+    })
+}
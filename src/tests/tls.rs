@@ -0,0 +1,65 @@
+use crate::tls;
+use apples_core::PlayerId;
+use apples_protocol::{FrameCodec, Message};
+use apples_utils::config::Tls;
+use futures_util::{SinkExt, StreamExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::pki_types::{pem::PemObject, CertificateDer};
+use tokio_util::codec::Framed;
+
+fn config(dir: &std::path::Path, pin: Option<String>) -> Tls {
+    serde_json::from_value(serde_json::json!({
+        "enabled": true,
+        "cert_path": dir.join("cert.pem"),
+        "key_path": dir.join("key.pem"),
+        "pinned_sha256": pin,
+    }))
+    .expect("tls config")
+}
+
+#[tokio::test]
+async fn carries_frames_to_pinned_self_signed_hosts() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join(format!("apples-tls-{}", std::process::id()));
+    let acceptor = tls::acceptor(&config(&dir, None))?;
+    let cert = CertificateDer::from_pem_file(dir.join("cert.pem"))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(dir.join("key.pem"))?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600, "Only the owner may read the key");
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let host = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await?;
+        let socket = tls::accept(&acceptor, Box::new(stream)).await?;
+        let mut framed = Framed::new(socket, FrameCodec::with_default_limit());
+        let frame = framed.next().await.expect("frame")?;
+        anyhow::Ok(Message::decode(&frame)?)
+    });
+
+    let pinned = config(&dir, Some(tls::fingerprint(&cert).to_uppercase()));
+    let socket = tls::connect(&pinned, Box::new(TcpStream::connect(addr).await?)).await?;
+    let mut framed = Framed::new(socket, FrameCodec::with_default_limit());
+    let welcome = Message::Welcome {
+        player_id: PlayerId(1),
+    };
+    framed.send(welcome.encode()?).await?;
+    assert_eq!(host.await??, welcome);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let acceptor = tls::acceptor(&config(&dir, None))?;
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await?;
+        let _ = acceptor.accept(stream).await;
+        anyhow::Ok(())
+    });
+    let wrong = config(&dir, Some("00".repeat(32)));
+    let refused = tls::connect(&wrong, Box::new(TcpStream::connect(addr).await?)).await;
+    assert!(refused.is_err());
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
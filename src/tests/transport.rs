@@ -0,0 +1,147 @@
+use crate::host_main::serve;
+use crate::transport::{Endpoint, Listener, MemoryConnector};
+use apples_core::cards::card::Card;
+use apples_protocol::{handshake, FrameCodec, Hello, Message};
+use apples_utils::{config::Config, consts::CONFIG_TOML};
+use futures_util::{SinkExt, StreamExt};
+use std::path::PathBuf;
+use tokio::{net::TcpListener, task::JoinSet};
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tokio_util::codec::Framed;
+
+#[test]
+fn parses_endpoints() {
+    assert_eq!(
+        "127.0.0.1:8080".parse::<Endpoint>().ok(),
+        Some(Endpoint::Tcp(([127, 0, 0, 1], 8080).into()))
+    );
+    let ws = "ws://127.0.0.1:8081/".parse::<Endpoint>().expect("websocket");
+    assert_eq!(ws, Endpoint::WebSocket(([127, 0, 0, 1], 8081).into()));
+    assert_eq!(ws.to_string(), "ws://127.0.0.1:8081");
+    let unix = "unix:/tmp/apples.sock".parse::<Endpoint>().expect("unix");
+    assert_eq!(unix, Endpoint::Unix(PathBuf::from("/tmp/apples.sock")));
+    assert_eq!(unix.to_string(), "unix:/tmp/apples.sock");
+    assert!("apples".parse::<Endpoint>().is_err());
+}
+
+#[tokio::test]
+async fn carries_frames_over_unix_sockets() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("apples-{}.sock", std::process::id()));
+    let endpoint = Endpoint::Unix(path.clone());
+    let mut listener = Listener::bind(&endpoint).await?;
+    let client = endpoint.connect().await?;
+    let server = listener.accept().await?;
+
+    let mut client = Framed::new(client, FrameCodec::with_default_limit());
+    let mut server = Framed::new(server, FrameCodec::with_default_limit());
+    client.send(Message::Ping.encode()?).await?;
+    let frame = server.next().await.expect("frame")?;
+    assert_eq!(Message::decode(&frame)?, Message::Ping);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn carries_one_frame_per_websocket_message() -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let endpoint = Endpoint::WebSocket(addr);
+    let mut listener = Listener::WebSocket {
+        listener,
+        upgrades: JoinSet::new(),
+    };
+
+    let (client, server) = tokio::join!(endpoint.connect(), listener.accept());
+    let mut client = Framed::new(client?, FrameCodec::with_default_limit());
+    let mut server = Framed::new(server?, FrameCodec::with_default_limit());
+    client.feed(Message::Ping.encode()?).await?;
+    client.feed(Message::Pong.encode()?).await?;
+    client.flush().await?;
+    let first = server.next().await.expect("ping")?;
+    let second = server.next().await.expect("pong")?;
+    assert_eq!(Message::decode(&first)?, Message::Ping);
+    assert_eq!(Message::decode(&second)?, Message::Pong);
+
+    let (raw, server) = tokio::join!(
+        async {
+            let stream = tokio::net::TcpStream::connect(addr).await?;
+            let (raw, _) = tokio_tungstenite::client_async(endpoint.to_string(), stream).await?;
+            anyhow::Ok(raw)
+        },
+        listener.accept()
+    );
+    let mut raw = raw?;
+    let mut server = Framed::new(server?, FrameCodec::with_default_limit());
+    raw.send(WsMessage::Binary(b"not a frame at all".to_vec().into()))
+        .await?;
+    assert!(
+        matches!(server.next().await, Some(Err(_))),
+        "A message that is not exactly one frame should be rejected"
+    );
+
+    Ok(())
+}
+
+/// A whole game against two bots without opening a port.
+#[tokio::test]
+async fn plays_a_game_in_memory() -> anyhow::Result<()> {
+    let config = Config::parse_config(CONFIG_TOML.into())?;
+    let (listener, connector) = Listener::memory();
+    let host = tokio::spawn(serve(config, vec![listener], 1, 2, None));
+    play_hot_seat(&connector).await?;
+    host.await??;
+    Ok(())
+}
+
+#[tokio::test]
+async fn keeps_accepting_after_a_failed_accept() -> anyhow::Result<()> {
+    let config = Config::parse_config(CONFIG_TOML.into())?;
+    let (listener, connector) = Listener::memory();
+    connector.abort()?;
+    let host = tokio::spawn(serve(config, vec![listener], 1, 2, None));
+    play_hot_seat(&connector).await?;
+    host.await??;
+    Ok(())
+}
+
+/// Joins through `connector` and plays the first cards until the game is over.
+async fn play_hot_seat(connector: &MemoryConnector) -> anyhow::Result<()> {
+    let mut framed = Framed::new(connector.connect()?, FrameCodec::with_default_limit());
+    let format = handshake::initiate(&mut framed, Hello::default())
+        .await?
+        .format;
+    framed
+        .send(
+            Message::JoinRequest {
+                name: String::from("hot seat"),
+            }
+            .encode_with(&format)?,
+        )
+        .await?;
+
+    let mut hand = Vec::new();
+    while let Some(frame) = framed.next().await {
+        let reply = match Message::decode_with(&frame?, &format)? {
+            Message::DealHand { cards } => {
+                hand.extend(cards);
+                continue;
+            }
+            Message::ReplaceHand { cards } => {
+                hand = cards;
+                continue;
+            }
+            Message::PlayRequest { count } => Message::SubmitRed {
+                cards: hand.drain(..count.min(hand.len())).collect(),
+            },
+            Message::JudgeRequest { submissions, .. } => Message::JudgeChoice {
+                card: submissions[0][0].id(),
+            },
+            Message::Ping => Message::Pong,
+            Message::GameOver { .. } => break,
+            _ => continue,
+        };
+        framed.send(reply.encode_with(&format)?).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,202 @@
+use crate::transport::Transport;
+use apples_utils::config::Tls;
+use sha2::{Digest, Sha256};
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::Path,
+    sync::Arc,
+};
+use thiserror::Error;
+use tokio_rustls::{
+    rustls::{
+        self,
+        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        crypto::{self, CryptoProvider},
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer, ServerName, UnixTime},
+        ClientConfig, DigitallySignedStruct, RootCertStore, ServerConfig, SignatureScheme,
+    },
+    TlsAcceptor, TlsConnector, TlsStream,
+};
+
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("failed to read certificate or key: {0}")]
+    Pem(#[from] rustls::pki_types::pem::Error),
+    #[error("failed to generate a self-signed certificate: {0}")]
+    Generate(#[from] rcgen::Error),
+    #[error("invalid server name: {0}")]
+    ServerName(#[from] rustls::pki_types::InvalidDnsNameError),
+    #[error("neither a pinned fingerprint nor the host certificate is configured")]
+    NoTrustAnchor,
+    #[error(transparent)]
+    Rustls(#[from] rustls::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+pub type TlsResult<T> = Result<T, TlsError>;
+
+/// Hex SHA-256 of a DER certificate, the form `pinned_sha256` expects.
+pub fn fingerprint(cert: &CertificateDer<'_>) -> String {
+    Sha256::digest(cert)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(crypto::ring::default_provider())
+}
+
+/// Loads the host certificate, generating a self-signed one first if allowed and missing.
+fn load_or_generate(tls: &Tls) -> TlsResult<(CertificateDer<'static>, PrivateKeyDer<'static>)> {
+    if tls.self_signed() && !(tls.cert_path().exists() && tls.key_path().exists()) {
+        let generated = rcgen::generate_simple_self_signed(vec![tls.server_name().to_owned()])?;
+        for path in [tls.cert_path(), tls.key_path()] {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+        }
+        std::fs::write(tls.cert_path(), generated.cert.pem())?;
+        write_private(
+            tls.key_path(),
+            generated.signing_key.serialize_pem().as_bytes(),
+        )?;
+        tracing::info!(
+            "Generated a self-signed certificate for {} at {}",
+            tls.server_name(),
+            tls.cert_path().display()
+        );
+    }
+    let cert = CertificateDer::from_pem_file(tls.cert_path())?;
+    let key = PrivateKeyDer::from_pem_file(tls.key_path())?;
+    Ok((cert, key))
+}
+
+/// Writes `contents` to a file only its owner can read, replacing any old one.
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // `mode` only applies to new files.
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(contents)
+}
+
+/// Builds the acceptor the host wraps incoming connections with.
+pub fn acceptor(tls: &Tls) -> TlsResult<TlsAcceptor> {
+    let (cert, key) = load_or_generate(tls)?;
+    tracing::info!("Serving TLS with certificate sha256 {}", fingerprint(&cert));
+    let config = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(vec![cert], key)?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Completes the host side of the TLS handshake on `stream`.
+pub async fn accept(
+    acceptor: &TlsAcceptor,
+    stream: Box<dyn Transport>,
+) -> TlsResult<Box<dyn Transport>> {
+    let stream = acceptor.accept(stream).await?;
+    Ok(Box::new(TlsStream::from(stream)))
+}
+
+/// Opens TLS over `stream`, trusting only the pinned certificate if one is
+/// configured and the certificate at `cert_path` otherwise.
+pub async fn connect(tls: &Tls, stream: Box<dyn Transport>) -> TlsResult<Box<dyn Transport>> {
+    let builder =
+        ClientConfig::builder_with_provider(provider()).with_safe_default_protocol_versions()?;
+    let config = match tls.pinned_sha256() {
+        Some(pin) => builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(Pinned::new(pin)))
+            .with_no_client_auth(),
+        None => {
+            let mut roots = RootCertStore::empty();
+            let cert = CertificateDer::from_pem_file(tls.cert_path())
+                .map_err(|_| TlsError::NoTrustAnchor)?;
+            roots.add(cert)?;
+            builder.with_root_certificates(roots).with_no_client_auth()
+        }
+    };
+    let name = ServerName::try_from(tls.server_name().to_owned())?;
+    let stream = TlsConnector::from(Arc::new(config))
+        .connect(name, stream)
+        .await?;
+    Ok(Box::new(TlsStream::from(stream)))
+}
+
+/// Accepts exactly one certificate, identified by its SHA-256, whoever issued it.
+#[derive(Debug)]
+struct Pinned {
+    fingerprint: String,
+    provider: Arc<CryptoProvider>,
+}
+
+impl Pinned {
+    fn new(pin: &str) -> Self {
+        Self {
+            fingerprint: pin.replace(':', "").to_ascii_lowercase(),
+            provider: provider(),
+        }
+    }
+}
+
+impl ServerCertVerifier for Pinned {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if fingerprint(end_entity) == self.fingerprint {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::InvalidCertificate(
+                rustls::CertificateError::ApplicationVerificationFailure,
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
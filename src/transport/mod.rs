@@ -0,0 +1,251 @@
+pub mod websocket;
+
+use std::{fmt, io, net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, DuplexStream},
+    net::{TcpListener, TcpStream, UnixListener, UnixStream},
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinSet,
+};
+use tokio_rustls::TlsStream;
+use websocket::WebSocket;
+
+/// Buffer size of each direction of an in-memory connection.
+const MEMORY_BUFFER: usize = 64 * 1024;
+
+/// How long a listener waits before accepting again once it ran out of file
+/// descriptors, so the others get a chance to close theirs.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Byte stream a `Framed<_, FrameCodec>` can sit on.
+pub trait Transport: AsyncRead + AsyncWrite + Send + Unpin + 'static {
+    /// Address of the other end, for logs.
+    fn peer(&self) -> String;
+}
+
+impl fmt::Debug for dyn Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Transport").field(&self.peer()).finish()
+    }
+}
+
+impl Transport for TcpStream {
+    fn peer(&self) -> String {
+        self.peer_addr()
+            .map_or_else(|_| String::from("tcp"), |addr| addr.to_string())
+    }
+}
+
+impl Transport for UnixStream {
+    fn peer(&self) -> String {
+        self.peer_addr()
+            .ok()
+            .and_then(|addr| addr.as_pathname().map(|path| path.display().to_string()))
+            .unwrap_or_else(|| String::from("unix socket"))
+    }
+}
+
+impl Transport for DuplexStream {
+    fn peer(&self) -> String {
+        String::from("in-memory peer")
+    }
+}
+
+impl<T: Transport> Transport for TlsStream<T> {
+    fn peer(&self) -> String {
+        self.get_ref().0.peer()
+    }
+}
+
+impl Transport for Box<dyn Transport> {
+    fn peer(&self) -> String {
+        self.as_ref().peer()
+    }
+}
+
+/// Where a host listens and clients connect: `host:port`, `ws://host:port`
+/// or `unix:<path>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    Tcp(SocketAddr),
+    WebSocket(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl FromStr for Endpoint {
+    type Err = std::net::AddrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix("unix:") {
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+        match s.strip_prefix("ws://") {
+            Some(addr) => addr.trim_end_matches('/').parse().map(Self::WebSocket),
+            None => s.parse().map(Self::Tcp),
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{addr}"),
+            Self::WebSocket(addr) => write!(f, "ws://{addr}"),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+impl Endpoint {
+    pub async fn connect(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(match self {
+            Self::Tcp(addr) => Box::new(TcpStream::connect(addr).await?),
+            Self::WebSocket(addr) => {
+                let stream = TcpStream::connect(addr).await?;
+                let (ws, _) = tokio_tungstenite::client_async(self.to_string(), stream)
+                    .await
+                    .map_err(io::Error::other)?;
+                Box::new(WebSocket::new(ws, self.to_string()))
+            }
+            Self::Unix(path) => Box::new(UnixStream::connect(path).await?),
+        })
+    }
+}
+
+/// Accepts connections on one of the supported transports.
+#[derive(Debug)]
+pub enum Listener {
+    Tcp(TcpListener),
+    /// Upgrades run in the background so a slow browser cannot stall the others.
+    WebSocket {
+        listener: TcpListener,
+        upgrades: JoinSet<io::Result<WebSocket<TcpStream>>>,
+    },
+    Unix(UnixListener),
+    Memory(UnboundedReceiver<io::Result<DuplexStream>>),
+}
+
+/// Opens in-memory connections to a `Listener::memory`.
+#[derive(Debug, Clone)]
+pub struct MemoryConnector(UnboundedSender<io::Result<DuplexStream>>);
+
+/// Whether an `accept` error only concerns the connection being accepted, or
+/// a shortage that passes, rather than the listener itself.
+fn is_transient(e: &io::Error) -> bool {
+    use io::ErrorKind::*;
+    matches!(
+        e.kind(),
+        ConnectionAborted | ConnectionReset | ConnectionRefused | Interrupted | TimedOut
+    ) || out_of_descriptors(e)
+}
+
+/// `ENFILE` or `EMFILE`.
+fn out_of_descriptors(e: &io::Error) -> bool {
+    cfg!(unix) && matches!(e.raw_os_error(), Some(23 | 24))
+}
+
+impl Listener {
+    /// Binds `endpoint`, replacing a stale Unix socket file left by an earlier host.
+    pub async fn bind(endpoint: &Endpoint) -> io::Result<Self> {
+        Ok(match endpoint {
+            Endpoint::Tcp(addr) => Self::Tcp(TcpListener::bind(addr).await?),
+            Endpoint::WebSocket(addr) => Self::WebSocket {
+                listener: TcpListener::bind(addr).await?,
+                upgrades: JoinSet::new(),
+            },
+            Endpoint::Unix(path) => {
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+                Self::Unix(UnixListener::bind(path)?)
+            }
+        })
+    }
+
+    #[inline]
+    pub fn is_websocket(&self) -> bool {
+        matches!(self, Self::WebSocket { .. })
+    }
+
+    /// A listener that needs no port or file, for tests and hot-seat games.
+    pub fn memory() -> (Self, MemoryConnector) {
+        let (connector, incoming) = unbounded_channel();
+        (Self::Memory(incoming), MemoryConnector(connector))
+    }
+
+    /// Waits for the next connection, skipping connections that failed while
+    /// being accepted; a memory listener whose connectors are all gone simply
+    /// never yields another one.
+    pub async fn accept(&mut self) -> io::Result<Box<dyn Transport>> {
+        loop {
+            match self.accept_one().await {
+                Err(e) if is_transient(&e) => {
+                    tracing::warn!("Failed to accept a connection: {e}");
+                    if out_of_descriptors(&e) {
+                        tokio::time::sleep(ACCEPT_BACKOFF).await;
+                    }
+                }
+                accepted => return accepted,
+            }
+        }
+    }
+
+    async fn accept_one(&mut self) -> io::Result<Box<dyn Transport>> {
+        Ok(match self {
+            Self::Tcp(listener) => Box::new(listener.accept().await?.0),
+            Self::WebSocket { listener, upgrades } => loop {
+                tokio::select! {
+                    accepted = listener.accept() => match accepted {
+                        Ok((stream, _)) => {
+                            upgrades.spawn(websocket::upgrade(stream));
+                        }
+                        Err(e) if is_transient(&e) => {
+                            tracing::warn!("Failed to accept a WebSocket connection: {e}");
+                            if out_of_descriptors(&e) {
+                                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                            }
+                        }
+                        Err(e) => return Err(e),
+                    },
+                    Some(upgraded) = upgrades.join_next() => match upgraded {
+                        Ok(Ok(ws)) => break Box::new(ws),
+                        Ok(Err(e)) => tracing::warn!("WebSocket upgrade failed: {e}"),
+                        Err(e) => tracing::warn!("WebSocket upgrade panicked: {e}"),
+                    },
+                }
+            },
+            Self::Unix(listener) => Box::new(listener.accept().await?.0),
+            Self::Memory(incoming) => match incoming.recv().await {
+                Some(stream) => Box::new(stream?),
+                None => std::future::pending().await,
+            },
+        })
+    }
+}
+
+/// Waits for a connection on any of `listeners`, returning which one took it.
+pub async fn accept_any(listeners: &mut [Listener]) -> io::Result<(usize, Box<dyn Transport>)> {
+    let accepts = listeners
+        .iter_mut()
+        .map(|listener| Box::pin(listener.accept()));
+    let (accepted, index, _) = futures_util::future::select_all(accepts).await;
+    Ok((index, accepted?))
+}
+
+impl MemoryConnector {
+    pub fn connect(&self) -> io::Result<Box<dyn Transport>> {
+        let (client, host) = tokio::io::duplex(MEMORY_BUFFER);
+        self.0
+            .send(Ok(host))
+            .map_err(|_| io::Error::from(io::ErrorKind::ConnectionRefused))?;
+        Ok(Box::new(client))
+    }
+
+    /// Makes the listener's next `accept` fail the way a peer that hung up
+    /// mid-accept does.
+    pub fn abort(&self) -> io::Result<()> {
+        self.0
+            .send(Err(io::ErrorKind::ConnectionAborted.into()))
+            .map_err(|_| io::Error::from(io::ErrorKind::ConnectionRefused))
+    }
+}
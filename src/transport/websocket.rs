@@ -0,0 +1,154 @@
+use super::Transport;
+use apples_protocol::Header;
+use bytes::{Buf, Bytes, BytesMut};
+use futures_util::{ready, Sink, Stream};
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+use tokio_tungstenite::{tungstenite::Message as WsMessage, WebSocketStream};
+
+/// How long a browser gets to complete the WebSocket upgrade.
+const UPGRADE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Carries frames over WebSocket, one frame per binary message, so
+/// `FrameCodec` sees the same byte stream as on TCP.
+pub struct WebSocket<S> {
+    ws: WebSocketStream<S>,
+    peer: String,
+    /// Rest of the last received message not yet read.
+    read: Bytes,
+    /// Written bytes not yet sent because their frame is incomplete.
+    write: BytesMut,
+}
+
+/// Completes the WebSocket upgrade of a freshly accepted socket.
+pub async fn upgrade(stream: TcpStream) -> io::Result<WebSocket<TcpStream>> {
+    let peer = stream.peer();
+    let upgrade = tokio_tungstenite::accept_async(stream);
+    let ws = tokio::time::timeout(UPGRADE_TIMEOUT, upgrade)
+        .await
+        .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))?
+        .map_err(io::Error::other)?;
+    Ok(WebSocket::new(ws, format!("ws://{peer}")))
+}
+
+/// Length of the frame at the start of `bytes`, once its header arrived.
+fn frame_len(bytes: &[u8]) -> io::Result<Option<usize>> {
+    if bytes.len() < Header::SIZE {
+        return Ok(None);
+    }
+    let header = Header::parse(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    Ok(Some(header.frame_len()))
+}
+
+impl<S> WebSocket<S> {
+    pub fn new(ws: WebSocketStream<S>, peer: String) -> Self {
+        Self {
+            ws,
+            peer,
+            read: Bytes::new(),
+            write: BytesMut::new(),
+        }
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> WebSocket<S> {
+    /// Sends every complete frame buffered in `write`.
+    fn poll_send_frames(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while let Some(len) = frame_len(&self.write)? {
+            if self.write.len() < len {
+                break;
+            }
+            ready!(Pin::new(&mut self.ws).poll_ready(cx)).map_err(io::Error::other)?;
+            let frame = self.write.split_to(len).freeze();
+            Pin::new(&mut self.ws)
+                .start_send(WsMessage::Binary(frame))
+                .map_err(io::Error::other)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncRead for WebSocket<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        while this.read.is_empty() {
+            match ready!(Pin::new(&mut this.ws).poll_next(cx)) {
+                Some(Ok(WsMessage::Binary(data))) => {
+                    if frame_len(&data)? != Some(data.len()) {
+                        return Poll::Ready(Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "websocket message does not hold exactly one frame",
+                        )));
+                    }
+                    this.read = data;
+                }
+                Some(Ok(WsMessage::Text(_))) => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "frames must be sent as binary messages",
+                    )));
+                }
+                Some(Ok(WsMessage::Close(_))) | None => return Poll::Ready(Ok(())),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Poll::Ready(Err(io::Error::other(e))),
+            }
+        }
+        let len = this.read.len().min(buf.remaining());
+        buf.put_slice(&this.read[..len]);
+        this.read.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> AsyncWrite for WebSocket<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_send_frames(cx))?;
+        this.write.extend_from_slice(buf);
+        if let Poll::Ready(Err(e)) = this.poll_send_frames(cx) {
+            return Poll::Ready(Err(e));
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_send_frames(cx))?;
+        Pin::new(&mut this.ws)
+            .poll_flush(cx)
+            .map_err(io::Error::other)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_send_frames(cx))?;
+        Pin::new(&mut this.ws)
+            .poll_close(cx)
+            .map_err(io::Error::other)
+    }
+}
+
+impl<S> Transport for WebSocket<S>
+where
+    S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    fn peer(&self) -> String {
+        self.peer.clone()
+    }
+}